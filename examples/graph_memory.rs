@@ -0,0 +1,187 @@
+// Heap used by an ElementGraph built from a synthetic model, next to the previous
+// representation with String ids on every connector and path
+//
+// cargo run --release --example graph_memory -- 500000
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+use model_parser_mcp::model::{cubs_model::Relationship, element_graph::ElementGraph};
+
+struct CountingAllocator;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+const TYPES: [&str; 8] = [
+    "feeds", "contains", "controls", "measures", "supplies", "drains", "powers", "monitors",
+];
+
+// Previous ElementGraph, one connector per element holding its paths
+struct LegacyGraph {
+    connectors: HashMap<String, LegacyConnector>,
+    connected_relationship: Vec<String>,
+}
+
+// Only built to be measured
+#[allow(dead_code)]
+struct LegacyConnector {
+    element_id: String,
+    in_ids: Vec<LegacyPath>,
+    out_ids: Vec<LegacyPath>,
+}
+
+//Relationship id : id
+#[allow(dead_code)]
+struct LegacyPath(String, String);
+
+impl LegacyGraph {
+    fn add_connector(&mut self, id: &str) {
+        if !self.connectors.contains_key(id) {
+            self.connectors.insert(
+                id.to_owned(),
+                LegacyConnector {
+                    element_id: id.to_owned(),
+                    in_ids: Vec::new(),
+                    out_ids: Vec::new(),
+                },
+            );
+        }
+    }
+
+    fn connect(&mut self, relationship_id: &str, from_id: &str, to_id: &str) {
+        let mut connected_in = false;
+        let mut connected_out = false;
+        if let Some(from_obj) = self.connectors.get_mut(from_id) {
+            from_obj
+                .out_ids
+                .push(LegacyPath(relationship_id.to_owned(), to_id.to_owned()));
+            connected_in = true;
+        }
+        if let Some(to_obj) = self.connectors.get_mut(to_id) {
+            to_obj
+                .in_ids
+                .push(LegacyPath(relationship_id.to_owned(), from_id.to_owned()));
+            connected_out = true;
+        }
+        if connected_in && connected_out {
+            self.connected_relationship.push(relationship_id.to_owned());
+        }
+    }
+}
+
+fn mib(bytes: usize) -> f64 {
+    bytes as f64 / (1024.0 * 1024.0)
+}
+
+fn element_id(i: usize) -> String {
+    format!("{:08x}-0000-4000-8000-{:012x}", i, i)
+}
+
+fn main() {
+    let element_count: usize = std::env::args()
+        .nth(1)
+        .and_then(|n| n.parse().ok())
+        .unwrap_or(500_000);
+
+    // Tree like model with one extra cross link every 4 elements
+    let element_ids: Vec<String> = (0..element_count).map(element_id).collect();
+    let mut relationships: Vec<Relationship> = Vec::new();
+    for i in 1..element_count {
+        relationships.push(relationship(
+            relationships.len(),
+            &element_ids[i / 3],
+            &element_ids[i],
+        ));
+        if i % 4 == 0 {
+            relationships.push(relationship(
+                relationships.len(),
+                &element_ids[i],
+                &element_ids[(i * 7) % element_count],
+            ));
+        }
+    }
+
+    // Previous representation
+    let before = ALLOCATED.load(Ordering::Relaxed);
+    let start_time = Instant::now();
+    let mut legacy_graph = LegacyGraph {
+        connectors: HashMap::new(),
+        connected_relationship: Vec::new(),
+    };
+    for id in &element_ids {
+        legacy_graph.add_connector(id);
+    }
+    for r in &relationships {
+        legacy_graph.connect(&r.id, &r.source_id, &r.target_id);
+    }
+    let legacy_time = start_time.elapsed();
+    let legacy_heap = ALLOCATED.load(Ordering::Relaxed) - before;
+    let legacy_out_count = legacy_graph.connectors[&element_ids[0]].out_ids.len();
+    drop(legacy_graph);
+
+    // Interned ids with CSR adjacency
+    let before = ALLOCATED.load(Ordering::Relaxed);
+    let start_time = Instant::now();
+    let mut graph = ElementGraph::new();
+    for id in &element_ids {
+        graph.add_connector(id);
+    }
+    for r in &relationships {
+        graph.connect_relationship(r);
+    }
+    // First traversal, lazy structures are built here
+    let out_count = graph
+        .get_connection(&element_ids[0])
+        .map_or(0, |c| c.get_out_id().len());
+    let elapsed_time = start_time.elapsed();
+    let heap = ALLOCATED.load(Ordering::Relaxed) - before;
+    assert_eq!(legacy_out_count, out_count);
+
+    println!(
+        "elements: {}, relationships: {}",
+        element_count,
+        relationships.len()
+    );
+    println!(
+        "String ids per connector and path: graph heap {:.1} MiB, build {:?}",
+        mib(legacy_heap),
+        legacy_time
+    );
+    println!(
+        "Interned ids with CSR adjacency: graph heap {:.1} MiB, build {:?}",
+        mib(heap),
+        elapsed_time
+    );
+}
+
+fn relationship(index: usize, source_id: &str, target_id: &str) -> Relationship {
+    Relationship {
+        id: format!("r{:07x}-0000-4000-8000-{:012x}", index, index),
+        source_id: source_id.to_owned(),
+        target_id: target_id.to_owned(),
+        type_: TYPES[index % TYPES.len()].to_owned(),
+        nature: "flow".to_owned(),
+        name: format!("Relationship {}", index),
+        version: 1,
+        dynamic_facets: HashMap::new(),
+        facets: HashMap::new(),
+        core_facets: HashMap::new(),
+    }
+}
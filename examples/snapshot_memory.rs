@@ -0,0 +1,150 @@
+// Load time and heap of a ModelSnapshot in eager and lazy facet mode
+//
+// cargo run --release --example snapshot_memory -- 200000
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+use serde_json::{Value, json};
+
+use model_parser_mcp::model::{
+    cubs_model::{FacetType, ModelData},
+    model_snapshot::{ModelSnapshot, SnapshotMode},
+    size_estimate::EstimateSize,
+};
+
+struct CountingAllocator;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let allocated = ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+        PEAK.fetch_max(allocated, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+const TYPES: [&str; 4] = ["Pump", "Tank", "Valve", "Sensor"];
+
+fn element_id(i: usize) -> String {
+    format!("{:08x}-0000-4000-8000-{:012x}", i, i)
+}
+
+fn mib(bytes: usize) -> f64 {
+    bytes as f64 / (1024.0 * 1024.0)
+}
+
+fn main() {
+    let element_count: usize = std::env::args()
+        .nth(1)
+        .and_then(|n| n.parse().ok())
+        .unwrap_or(200_000);
+
+    // Tree like model, each element with a few nested facets
+    let elements: Vec<Value> = (0..element_count)
+        .map(|i| {
+            json!({
+                "id": element_id(i),
+                "type": TYPES[i % TYPES.len()],
+                "nature": "Asset",
+                "name": format!("Element {}", i),
+                "version": 1,
+                "serialNumber": format!("SN-{:08}", i),
+                "dynamicFacets": {
+                    "status": {"value": "running", "updatedAt": "2025-01-01T00:00:00Z"},
+                    "flowRate": {"value": i as f64 * 0.5, "unit": "m3/h"}
+                },
+                "facets": {
+                    "design": {"pressure": {"value": 16, "unit": "bar"}, "material": "steel"},
+                    "maintenance": {"intervalDays": 90, "history": [1, 2, 3]},
+                    "location": {"building": "B1", "floor": i % 5, "room": format!("R{}", i % 100)}
+                }
+            })
+        })
+        .collect();
+    let relationships: Vec<Value> = (1..element_count)
+        .map(|i| {
+            json!({
+                "id": format!("r{:07x}", i),
+                "sourceId": element_id(i / 3),
+                "targetId": element_id(i),
+                "type": "feeds",
+                "nature": "Flow",
+                "version": 1,
+                "facets": {}
+            })
+        })
+        .collect();
+    let payload = serde_json::to_vec(&json!({
+        "schemaVersion": "1",
+        "modelId": "m1",
+        "siteModelId": "s1",
+        "version": 1,
+        "elements": elements,
+        "relationships": relationships
+    }))
+    .unwrap();
+    drop(elements);
+    drop(relationships);
+    println!(
+        "elements: {}, payload: {:.1} MiB",
+        element_count,
+        mib(payload.len())
+    );
+
+    for mode in [SnapshotMode::Eager, SnapshotMode::Lazy] {
+        let before = ALLOCATED.load(Ordering::Relaxed);
+        PEAK.store(before, Ordering::Relaxed);
+
+        let start_time = Instant::now();
+        let model_data: ModelData = serde_json::from_slice(&payload).unwrap();
+        let snapshot = ModelSnapshot::build_with_mode(model_data, mode);
+        let load_time = start_time.elapsed();
+        let loaded = ALLOCATED.load(Ordering::Relaxed) - before;
+        let peak = PEAK.load(Ordering::Relaxed) - before;
+
+        // Type filter only, no facet read
+        let start_time = Instant::now();
+        let pumps = snapshot.get_elements_with_type_nature(Some("Pump"), None);
+        let type_time = start_time.elapsed();
+
+        // Pointer on every element, lazy facets are parsed here
+        let start_time = Instant::now();
+        let values = ModelData::get_json_values(
+            snapshot.get_model_data().get_elements(),
+            Some(FacetType::Facets),
+            "/design/pressure/value",
+            false,
+        );
+        let pointer_time = start_time.elapsed();
+        let after_pointer = ALLOCATED.load(Ordering::Relaxed) - before;
+
+        println!(
+            "{:?}: load {:?}, heap {:.1} MiB, estimated {:.1} MiB, peak {:.1} MiB, type filter {} in {:?}, pointer {} in {:?}, heap after pointer {:.1} MiB",
+            mode,
+            load_time,
+            mib(loaded),
+            mib(snapshot.estimate_size()),
+            mib(peak),
+            pumps.len(),
+            type_time,
+            values.len(),
+            pointer_time,
+            mib(after_pointer)
+        );
+        drop(values);
+        drop(pumps);
+        drop(snapshot);
+    }
+}
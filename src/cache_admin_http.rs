@@ -0,0 +1,66 @@
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{delete, get},
+};
+
+use model_parser_mcp::model::{
+    app_state::AppState, cache_admin::CacheAdmin, model_error::ModelError,
+};
+
+use crate::model_parser_tool::ModelStatsErrorResult;
+
+// Cache admin over HTTP, only mounted when CACHE_ADMIN_ENABLED is set
+//
+// GET    /admin/cache                        entries and counters
+// DELETE /admin/cache/{model_id}/{version}   evict a version, latest for the latest one
+// POST   /admin/cache/{model_id}/{version}   preload a version, latest for the latest one
+
+pub fn router(app_state: AppState) -> Router {
+    Router::new()
+        .route("/admin/cache", get(get_status))
+        .route(
+            "/admin/cache/{model_id}/{version}",
+            delete(evict).post(preload),
+        )
+        .with_state(app_state)
+}
+
+async fn get_status(State(app_state): State<AppState>) -> Response {
+    Json(CacheAdmin::get_status(&app_state)).into_response()
+}
+
+async fn evict(
+    State(app_state): State<AppState>,
+    Path((model_id, version)): Path<(String, String)>,
+) -> Response {
+    let result = CacheAdmin::evict(&app_state, &model_id, &version).await;
+    let status = match result.is_snapshot_evicted || result.is_centrality_evicted {
+        true => StatusCode::OK,
+        false => StatusCode::NOT_FOUND,
+    };
+    (status, Json(result)).into_response()
+}
+
+async fn preload(
+    State(app_state): State<AppState>,
+    Path((model_id, version)): Path<(String, String)>,
+) -> Response {
+    match CacheAdmin::preload(&app_state, &model_id, &version).await {
+        Ok(result) => Json(result).into_response(),
+        Err(e) => {
+            let status = match e {
+                ModelError::ModelNotFound(_, _) => StatusCode::NOT_FOUND,
+                ModelError::InvalidInput(_) => StatusCode::BAD_REQUEST,
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+            let error = ModelStatsErrorResult {
+                model_id,
+                error_msg: e.to_string(),
+            };
+            (status, Json(error)).into_response()
+        }
+    }
+}
@@ -0,0 +1,312 @@
+use std::path::{Path, PathBuf};
+
+use clap::{Args, Subcommand, ValueEnum};
+use serde_json::Value;
+
+use model_parser_mcp::model::{
+    app_state::QuickCache,
+    config::PageConfig,
+    model_dict::{CubsObjectReport, ModelDictionary},
+    model_parser::ModelParser,
+    model_snapshot::{ModelSnapshot, SnapshotMode},
+    model_source::ModelFileSource,
+    output_renderer::OutputGraphRenderer,
+};
+
+// Offline commands running the MCP queries against a model file, no server or DB
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Element and relationship counts by type and nature
+    Stats(FileArgs),
+    /// Filter elements by id, subgraph, type and nature
+    Query(QueryArgs),
+    /// Ancestors and children of an element
+    Lineage(LineageArgs),
+    /// Consistency checks, exits with 1 when an error is found
+    Validate(ValidateArgs),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    Json,
+    Table,
+}
+
+#[derive(Debug, Args)]
+pub struct FileArgs {
+    /// Model JSON or gzip file
+    file: PathBuf,
+    #[arg(long, value_enum, default_value_t = OutputFormat::Json)]
+    format: OutputFormat,
+    /// Write the result to a file instead of stdout
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+pub struct QueryArgs {
+    #[command(flatten)]
+    file_args: FileArgs,
+    /// Element id
+    #[arg(long, default_value = "")]
+    id: String,
+    /// Return the subgraph of the element id
+    #[arg(long)]
+    subgraph: bool,
+    #[arg(long, default_value = "All")]
+    types: String,
+    #[arg(long, default_value = "All")]
+    natures: String,
+    /// Json pointer applied to the facets
+    #[arg(long, default_value = "")]
+    query: String,
+    /// Facet type the json pointer is applied to
+    #[arg(long, default_value = "")]
+    facet_type: String,
+    #[arg(long)]
+    detail: bool,
+    /// Truncate values deeper than depth, 0 keeps everything
+    #[arg(long, default_value_t = 0)]
+    depth: usize,
+    #[arg(long, default_value_t = 50)]
+    page_size: usize,
+    #[arg(long, default_value_t = 1)]
+    page: usize,
+}
+
+#[derive(Debug, Args)]
+pub struct ValidateArgs {
+    #[command(flatten)]
+    file_args: FileArgs,
+    #[arg(long, default_value_t = 1000)]
+    page_size: usize,
+    #[arg(long, default_value_t = 1)]
+    page: usize,
+}
+
+#[derive(Debug, Args)]
+pub struct LineageArgs {
+    #[command(flatten)]
+    file_args: FileArgs,
+    element_id: String,
+    #[arg(long, default_value_t = 2)]
+    ancestor_depth: u32,
+    #[arg(long, default_value_t = 5)]
+    children_depth: u32,
+}
+
+pub async fn run(command: Command) -> anyhow::Result<()> {
+    let (file_args, rendered) = match command {
+        Command::Stats(file_args) => {
+            let (source, snapshot_cache) = load(&file_args.file).await?;
+            let model_parser = ModelParser::new(snapshot_cache, QuickCache::new(1), &source);
+            let dict = model_parser
+                .get_model_stats(source.get_model_id(), &source.get_version().to_string())
+                .await?;
+            let rendered = match file_args.format {
+                OutputFormat::Json => serde_json::to_string_pretty(&dict)?,
+                OutputFormat::Table => render_stats(&dict),
+            };
+            (file_args, rendered)
+        }
+        Command::Query(args) => {
+            let (source, snapshot_cache) = load(&args.file_args.file).await?;
+            let model_parser = ModelParser::new(snapshot_cache, QuickCache::new(1), &source);
+            let result = model_parser
+                .query_model(
+                    source.get_model_id().to_owned(),
+                    source.get_version().to_string(),
+                    args.id,
+                    args.subgraph,
+                    args.types,
+                    args.natures,
+                    args.query,
+                    args.depth,
+                    PageConfig {
+                        elements_per_page: args.page_size.max(1),
+                        page_to_get: args.page.max(1),
+                    },
+                    args.facet_type,
+                    args.detail,
+                )
+                .await?;
+            let rendered = match args.file_args.format {
+                OutputFormat::Json => result.data,
+                OutputFormat::Table => {
+                    let values: Vec<Value> = serde_json::from_str(&result.data)?;
+                    let rows = values
+                        .iter()
+                        .map(|v| {
+                            ["id", "type", "nature", "name"]
+                                .iter()
+                                .map(|field| match v.get(field) {
+                                    Some(Value::String(s)) => s.clone(),
+                                    Some(other) => other.to_string(),
+                                    None => String::new(),
+                                })
+                                .collect()
+                        })
+                        .collect();
+                    format!(
+                        "{}Page {} of {}, {} elements\n",
+                        render_table(&["ID", "TYPE", "NATURE", "NAME"], rows),
+                        result.page_count.current_page,
+                        result.page_count.total_page,
+                        result.total_result_count
+                    )
+                }
+            };
+            (args.file_args, rendered)
+        }
+        Command::Lineage(args) => {
+            let (source, snapshot_cache) = load(&args.file_args.file).await?;
+            let model_parser = ModelParser::new(snapshot_cache, QuickCache::new(1), &source);
+            let output = model_parser
+                .get_element_lineage(
+                    source.get_model_id(),
+                    &source.get_version().to_string(),
+                    &args.element_id,
+                    args.ancestor_depth,
+                    args.children_depth,
+                )
+                .await?;
+            let rendered = match args.file_args.format {
+                OutputFormat::Json => serde_json::to_string_pretty(&output)?,
+                OutputFormat::Table => OutputGraphRenderer::render_tree(&output),
+            };
+            (args.file_args, rendered)
+        }
+        Command::Validate(args) => {
+            let file_args = args.file_args;
+            let (source, snapshot_cache) = load(&file_args.file).await?;
+            let model_parser = ModelParser::new(snapshot_cache, QuickCache::new(1), &source);
+            let result = model_parser
+                .validate_model(
+                    source.get_model_id(),
+                    &source.get_version().to_string(),
+                    PageConfig {
+                        elements_per_page: args.page_size.max(1),
+                        page_to_get: args.page.max(1),
+                    },
+                )
+                .await?;
+            let rendered = match file_args.format {
+                OutputFormat::Json => serde_json::to_string_pretty(&result)?,
+                OutputFormat::Table => {
+                    let rows = result
+                        .groups
+                        .iter()
+                        .flat_map(|g| g.findings.iter())
+                        .map(|f| {
+                            vec![
+                                format!("{:?}", f.severity).to_lowercase(),
+                                f.relationship_id.clone().unwrap_or_default(),
+                                f.element_ids.join(","),
+                                f.message.clone(),
+                            ]
+                        })
+                        .collect();
+                    format!(
+                        "{}Page {} of {}, {} errors, {} warnings\n",
+                        render_table(&["SEVERITY", "RELATIONSHIP", "ELEMENTS", "MESSAGE"], rows),
+                        result.current_page,
+                        result.total_page,
+                        result.error_count,
+                        result.warning_count
+                    )
+                }
+            };
+            write_output(&file_args, &rendered)?;
+            if !result.is_valid {
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+    };
+
+    write_output(&file_args, &rendered)
+}
+
+// Decode the file once and seed the snapshot cache with it
+async fn load(file: &Path) -> anyhow::Result<(ModelFileSource, QuickCache<ModelSnapshot>)> {
+    let (source, model_data) = ModelFileSource::open(file.to_path_buf()).await?;
+    let snapshot_mode = SnapshotMode::from_env()?;
+    let snapshot = tokio::task::spawn_blocking(move || {
+        ModelSnapshot::build_with_mode(model_data, snapshot_mode)
+    })
+    .await?;
+    let snapshot_cache: QuickCache<ModelSnapshot> = QuickCache::new(1);
+    snapshot_cache.insert_arc(
+        source.get_model_id(),
+        &source.get_version().to_string(),
+        std::sync::Arc::new(snapshot),
+    );
+    Ok((source, snapshot_cache))
+}
+
+fn write_output(file_args: &FileArgs, rendered: &str) -> anyhow::Result<()> {
+    match &file_args.output {
+        Some(path) => std::fs::write(path, rendered)?,
+        None => println!("{}", rendered),
+    }
+    Ok(())
+}
+
+fn render_stats(dict: &ModelDictionary) -> String {
+    let mut rendered = format!("Model {} version {}\n", dict.model_id, dict.version);
+    let reports = [
+        ("Elements", &dict.model_stats.elements_stats),
+        ("Relationships", &dict.model_stats.relationships_stats),
+    ];
+    for (title, report) in reports {
+        let Some(report) = report else {
+            continue;
+        };
+        rendered.push_str(&format!("\n{} ({})\n", title, report.all_count));
+        rendered.push_str(&render_report(report));
+    }
+    rendered
+}
+
+fn render_report(report: &CubsObjectReport) -> String {
+    let rows = report
+        .by_type
+        .value
+        .iter()
+        .map(|c| vec!["type".to_string(), c.element.clone(), c.count.to_string()])
+        .chain(
+            report
+                .by_nature
+                .value
+                .iter()
+                .map(|c| vec!["nature".to_string(), c.element.clone(), c.count.to_string()]),
+        )
+        .collect();
+    render_table(&["BY", "VALUE", "COUNT"], rows)
+}
+
+// Left aligned columns sized to their widest cell
+fn render_table(headers: &[&str], rows: Vec<Vec<String>>) -> String {
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.chars().count()).collect();
+    for row in &rows {
+        for (i, cell) in row.iter().enumerate() {
+            widths[i] = widths[i].max(cell.chars().count());
+        }
+    }
+
+    let render_row = |cells: Vec<&str>| -> String {
+        let line: Vec<String> = cells
+            .iter()
+            .zip(widths.iter())
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect();
+        format!("{}\n", line.join("  ").trim_end())
+    };
+
+    let mut rendered = render_row(headers.to_vec());
+    for row in &rows {
+        rendered.push_str(&render_row(row.iter().map(|c| c.as_str()).collect()));
+    }
+    rendered
+}
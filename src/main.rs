@@ -1,19 +1,38 @@
-use model_parser_mcp::model::app_state::{self, AppState};
+use clap::Parser;
+use model_parser_mcp::model::{app_state::AppState, utils::Utils};
 use rmcp::transport::sse_server::{SseServer, SseServerConfig};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 use dotenv::dotenv;
 
 const BIND_ADDRESS: &str = "0.0.0.0:8001";
 
+mod cache_admin_http;
+mod cli;
 mod model_parser_tool;
 use model_parser_tool::ModelParserTool;
 
+/// Model parser MCP server, or offline queries on a model file with a subcommand
+#[derive(Debug, Parser)]
+#[command(version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<cli::Command>,
+}
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    // Init app state
-    // Load env file
+    // Load env file, the CLI reads MODEL_SNAPSHOT_MODE too
     dotenv().ok();
+
+    // Offline CLI, stdout is kept for the result
+    if let Some(command) = Cli::parse().command {
+        Utils::set_log_to_stderr();
+        return cli::run(command).await;
+    }
+
+    // Init app state
     let app_state = AppState::new().await;
+    app_state.spawn_cache_warming();
 
     tracing_subscriber::registry()
         .with(
@@ -34,6 +53,10 @@ async fn main() -> anyhow::Result<()> {
     let (sse_server, router) = SseServer::new(config);
 
     // Do something with the router, e.g., add routes or middleware
+    let router = match app_state.is_cache_admin_enabled() {
+        true => router.merge(cache_admin_http::router(app_state.clone())),
+        false => router,
+    };
 
     let listener = tokio::net::TcpListener::bind(sse_server.config.bind).await?;
 
@@ -1,116 +1,670 @@
-use std::sync::Arc;
-use std::time::Duration;
+use std::collections::HashMap;
+use std::env;
+use std::future::Future;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
-use mini_moka::sync::Cache;
+use mini_moka::sync::{Cache, ConcurrentCacheExt};
+use serde::{Deserialize, Serialize};
+use tokio::sync::OnceCell;
 
-use crate::model::cubs_model::ModelData;
+use crate::info;
+use crate::model::cache_warmer::{CacheWarmer, WarmTarget};
 use crate::model::database_util::connect_to_db;
-use crate::model::element_graph::ElementGraph;
+use crate::model::element_graph_centrality::CentralityReport;
+use crate::model::model_error::ModelError;
+use crate::model::model_parser::ModelParser;
+use crate::model::model_snapshot::{ModelSnapshot, SnapshotMode};
+use crate::model::model_source::{
+    FileModelSource, HttpModelSource, ModelSource, ModelSourceConfig, PostgresModelSource,
+};
+use crate::model::size_estimate::EstimateSize;
 // use quick_cache::sync::Cache;
-const CACHE_SIZE: usize = 2;
+const MIB: u64 = 1024 * 1024;
+// Entries are weighed in KiB to fit the u32 weight of moka
+const WEIGHT_UNIT: u64 = 1024;
+
+// Snapshot and centrality cache settings, read from the JSON file in
+// CACHE_CONFIG_FILE when set, then overridden by the env variables
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CacheConfig {
+    // Estimated heap of the cached snapshots
+    pub memory_budget_mb: u64,
+    pub centrality_memory_budget_mb: u64,
+    pub ttl_secs: u64,
+    pub tti_secs: u64,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            memory_budget_mb: 2048,
+            centrality_memory_budget_mb: 64,
+            ttl_secs: 3600,
+            tti_secs: 600,
+        }
+    }
+}
+
+impl CacheConfig {
+    pub fn from_env() -> Result<Self, ModelError> {
+        if env::var("CACHE_SIZE").is_ok() {
+            info!(
+                "[CacheConfig - from_env] CACHE_SIZE is deprecated and ignored, the cache is sized with CACHE_MEMORY_BUDGET_MB"
+            );
+        }
+        let mut config = match env::var("CACHE_CONFIG_FILE") {
+            Ok(path) => Self::from_file(Path::new(&path))?,
+            Err(_) => Self::default(),
+        };
+        config.apply_vars(|name| env::var(name).ok())?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self, ModelError> {
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            ModelError::InvalidInput(format!("cache config file {}, {}", path.display(), e))
+        })?;
+        serde_json::from_str(&content).map_err(|e| {
+            ModelError::InvalidInput(format!("cache config file {}, {}", path.display(), e))
+        })
+    }
+
+    fn apply_vars(&mut self, var: impl Fn(&str) -> Option<String>) -> Result<(), ModelError> {
+        let fields = [
+            ("CACHE_MEMORY_BUDGET_MB", &mut self.memory_budget_mb),
+            (
+                "CENTRALITY_CACHE_MEMORY_BUDGET_MB",
+                &mut self.centrality_memory_budget_mb,
+            ),
+            ("CACHE_TTL_SECS", &mut self.ttl_secs),
+            ("CACHE_TTI_SECS", &mut self.tti_secs),
+        ];
+        for (name, field) in fields {
+            if let Some(value) = var(name) {
+                *field = value.trim().parse::<u64>().map_err(|_| {
+                    ModelError::InvalidInput(format!("{} {}, expected a number", name, value))
+                })?;
+            }
+        }
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<(), ModelError> {
+        match (self.memory_budget_mb, self.centrality_memory_budget_mb) {
+            (0, _) | (_, 0) => Err(ModelError::InvalidInput(
+                "cache memory budgets must be greater than 0".to_string(),
+            )),
+            // A 0 expiry would drop every entry as soon as it is cached
+            _ if self.ttl_secs == 0 || self.tti_secs == 0 => Err(ModelError::InvalidInput(
+                "CACHE_TTL_SECS and CACHE_TTI_SECS must be greater than 0".to_string(),
+            )),
+            _ => Ok(()),
+        }
+    }
+}
 
 #[derive(Clone, Debug)]
 pub struct AppState {
-    pg_pool: sqlx::Pool<sqlx::Postgres>,
-    model_cache: QuickCache<ModelData>,
-    graph_cache: QuickCache<ElementGraph>,
+    model_source: Arc<dyn ModelSource>,
+    snapshot_mode: SnapshotMode,
+    snapshot_cache: QuickCache<ModelSnapshot>,
+    centrality_cache: QuickCache<CentralityReport>,
+    cache_warmer: CacheWarmer,
+    // Cache admin tools and HTTP endpoints, set with CACHE_ADMIN_ENABLED
+    is_cache_admin_enabled: bool,
 }
 
 impl AppState {
     pub async fn new() -> Self {
-        // DB pool
-        let pg_pool = connect_to_db().await;
-
-        // Moka Cache
-        let moka_model_cache: Cache<String, Arc<ModelData>> = Cache::builder()
-            .max_capacity(CACHE_SIZE as u64)
-            .time_to_live(Duration::from_secs(3600))
-            .time_to_idle(Duration::from_secs(600))
-            .build();
+        // Model source, DB pool only when reading from Postgres
+        let model_source: Arc<dyn ModelSource> =
+            match ModelSourceConfig::from_env().expect("Invalid model source configuration") {
+                ModelSourceConfig::Postgres(table_config) => Arc::new(PostgresModelSource::new(
+                    connect_to_db().await,
+                    &table_config,
+                )),
+                ModelSourceConfig::Filesystem(root) => Arc::new(FileModelSource::new(root)),
+                ModelSourceConfig::Http(base_url) => Arc::new(HttpModelSource::new(base_url)),
+            };
+        info!("Reading models from {} source", model_source.get_name());
+        let snapshot_mode = SnapshotMode::from_env().expect("Invalid snapshot mode");
+        info!("Building {:?} model snapshots", snapshot_mode);
+        let cache_warmer =
+            CacheWarmer::new(WarmTarget::from_env().expect("Invalid WARM_MODELS configuration"));
 
-        let moka_graph_cache: Cache<String, Arc<ElementGraph>> = Cache::builder()
-            .max_capacity(CACHE_SIZE as u64)
-            .time_to_live(Duration::from_secs(3600))
-            .time_to_idle(Duration::from_secs(600))
-            .build();
+        // Moka Cache weighed by estimated heap
+        let cache_config = CacheConfig::from_env().expect("Invalid cache configuration");
+        info!("Cache configuration {:?}", cache_config);
+        let ttl = Duration::from_secs(cache_config.ttl_secs);
+        let tti = Duration::from_secs(cache_config.tti_secs);
+        let is_cache_admin_enabled = env::var("CACHE_ADMIN_ENABLED")
+            .is_ok_and(|enabled| matches!(enabled.to_lowercase().as_str(), "true" | "1"));
+        info!("Cache admin enabled: {}", is_cache_admin_enabled);
 
         AppState {
-            pg_pool,
-            model_cache: QuickCache {
-                data: moka_model_cache,
-            },
-            graph_cache: QuickCache {
-                data: moka_graph_cache,
-            },
+            model_source,
+            snapshot_mode,
+            snapshot_cache: QuickCache::with_budget(
+                cache_config.memory_budget_mb * MIB,
+                ttl,
+                tti,
+            ),
+            centrality_cache: QuickCache::with_budget(
+                cache_config.centrality_memory_budget_mb * MIB,
+                ttl,
+                tti,
+            ),
+            cache_warmer,
+            is_cache_admin_enabled,
         }
     }
 
-    pub fn get_pg_pool_ref(&self) -> &sqlx::Pool<sqlx::Postgres> {
-        &self.pg_pool
+    // Load the WARM_MODELS into the snapshot cache without blocking the server start
+    pub fn spawn_cache_warming(&self) {
+        if self.cache_warmer.is_empty() {
+            return;
+        }
+        let app_state = self.clone();
+        tokio::spawn(async move {
+            let model_parser = app_state.model_parser();
+            app_state.cache_warmer.run(&model_parser).await;
+        });
+    }
+
+    // Parser over the shared caches and model source, in the configured snapshot mode
+    pub fn model_parser(&self) -> ModelParser<'_> {
+        ModelParser::new(
+            self.get_snapshot_cache(),
+            self.get_centrality_cache(),
+            self.get_model_source_ref(),
+        )
+        .with_snapshot_mode(self.get_snapshot_mode())
+    }
+
+    pub fn get_model_source_ref(&self) -> &dyn ModelSource {
+        self.model_source.as_ref()
+    }
+
+    pub fn get_snapshot_mode(&self) -> SnapshotMode {
+        self.snapshot_mode
+    }
+
+    pub fn get_snapshot_cache(&self) -> QuickCache<ModelSnapshot> {
+        self.snapshot_cache.clone()
     }
 
-    pub fn get_model_cache(&self) -> QuickCache<ModelData> {
-        self.model_cache.clone()
+    pub fn get_centrality_cache(&self) -> QuickCache<CentralityReport> {
+        self.centrality_cache.clone()
     }
 
-    pub fn get_graph_cache(&self) -> QuickCache<ElementGraph> {
-        self.graph_cache.clone()
+    pub fn get_cache_warmer(&self) -> &CacheWarmer {
+        &self.cache_warmer
+    }
+
+    pub fn is_cache_admin_enabled(&self) -> bool {
+        self.is_cache_admin_enabled
     }
 }
 
+// Cached value with what the cache admin reports
+#[derive(Debug)]
+struct CacheEntry<T> {
+    value: Arc<T>,
+    model_id: String,
+    version: String,
+    size: usize,
+    inserted_at: Instant,
+}
+
+#[derive(Debug, Default)]
+struct CacheCounters {
+    hit: AtomicU64,
+    miss: AtomicU64,
+    // New keys only, a replaced entry is not counted
+    insert: AtomicU64,
+    invalidation: AtomicU64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CacheEntryInfo {
+    pub model_id: String,
+    pub version: String,
+    pub estimated_size: usize,
+    pub age_secs: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CacheStats {
+    pub entry_count: u64,
+    pub estimated_size: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory_budget: Option<u64>,
+    pub hit_count: u64,
+    pub miss_count: u64,
+    pub insert_count: u64,
+    // Dropped for the budget or expired
+    pub eviction_count: u64,
+    pub invalidation_count: u64,
+}
+
+// Set once by the caller running the load
+type LoadingCell<T> = Arc<OnceCell<Arc<T>>>;
+
 #[derive(Debug)]
 pub struct QuickCache<T>
 where
-    T: Send + Sync + 'static,
+    T: EstimateSize + Send + Sync + 'static,
 {
-    data: Cache<String, Arc<T>>,
+    data: Cache<String, Arc<CacheEntry<T>>>,
+    // Bytes, None when capacity is an entry count
+    memory_budget: Option<u64>,
+    counters: Arc<CacheCounters>,
+    // Key : load in flight, shared by every clone
+    loading: Arc<Mutex<HashMap<String, LoadingCell<T>>>>,
 }
 
 impl<T> Clone for QuickCache<T>
 where
-    T: Send + Sync + 'static,
+    T: EstimateSize + Send + Sync + 'static,
 {
     fn clone(&self) -> Self {
         Self {
             data: self.data.clone(),
+            memory_budget: self.memory_budget,
+            counters: Arc::clone(&self.counters),
+            loading: Arc::clone(&self.loading),
         }
     }
 }
 
 impl<T> QuickCache<T>
 where
-    T: Send + Sync + 'static,
+    T: EstimateSize + Send + Sync + 'static,
 {
+    // Capacity in entries, no expiry
     pub fn new(capacity: u64) -> Self {
         Self {
             data: Cache::new(capacity),
+            memory_budget: None,
+            counters: Arc::default(),
+            loading: Arc::default(),
         }
     }
 
-    pub fn get_ref(&self, key: &str, version: &str) -> Option<Arc<T>>
-    where
-        T: Clone + Send + Sync + 'static,
-    {
-        println!(
+    // Capacity in estimated bytes
+    pub fn with_budget(memory_budget: u64, ttl: Duration, tti: Duration) -> Self {
+        let data = Cache::builder()
+            .weigher(|_key, entry: &Arc<CacheEntry<T>>| {
+                (entry.size as u64 / WEIGHT_UNIT).clamp(1, u32::MAX as u64) as u32
+            })
+            .max_capacity((memory_budget / WEIGHT_UNIT).max(1))
+            .time_to_live(ttl)
+            .time_to_idle(tti)
+            .build();
+        Self {
+            data,
+            memory_budget: Some(memory_budget),
+            counters: Arc::default(),
+            loading: Arc::default(),
+        }
+    }
+
+    pub fn get_ref(&self, key: &str, version: &str) -> Option<Arc<T>> {
+        info!(
             "[QuickCache]Retrieving from cache with key: {} and version:{} ",
             key, version
         );
         let key = format!("{}-{}", key, version);
-        self.data.get(&key).map(|arc| Arc::clone(&arc))
+        let value = self.data.get(&key).map(|entry| Arc::clone(&entry.value));
+        let counter = match value {
+            Some(_) => &self.counters.hit,
+            None => &self.counters.miss,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        value
+    }
+
+    // No log and not counted, used when scanning many keys
+    pub fn peek_ref(&self, key: &str, version: &str) -> Option<Arc<T>> {
+        self.data
+            .get(&format!("{}-{}", key, version))
+            .map(|entry| Arc::clone(&entry.value))
     }
 
-    pub fn insert(&self, key: &str, version: &str, value: &T)
+    // Insert an already shared value without cloning it
+    pub fn insert_arc(&self, key: &str, version: &str, value: Arc<T>) {
+        let size = value.estimate_size();
+        info!(
+            "[QuickCache] insert into cache with key: {} and version:{} of capacity: {}, estimated size: {} MiB",
+            key,
+            version,
+            self.data.entry_count(),
+            size as u64 / MIB
+        );
+        // Moka drops an entry heavier than the whole budget right away
+        if let Some(memory_budget) = self.memory_budget.filter(|budget| size as u64 > *budget) {
+            info!(
+                "[QuickCache] key: {} and version:{} is larger than the cache budget of {} MiB and will not be kept",
+                key,
+                version,
+                memory_budget / MIB
+            );
+        }
+        let entry = CacheEntry {
+            value,
+            model_id: key.to_owned(),
+            version: version.to_owned(),
+            size,
+            inserted_at: Instant::now(),
+        };
+        let key = format!("{}-{}", key, version);
+        if !self.data.contains_key(&key) {
+            self.counters.insert.fetch_add(1, Ordering::Relaxed);
+        }
+        self.data.insert(key, Arc::new(entry));
+    }
+
+    // Cached value, else loaded and inserted once while concurrent callers of the key await it.
+    // A failed load is not shared, the next waiting caller runs its own
+    pub async fn get_or_load<F, Fut>(
+        &self,
+        key: &str,
+        version: &str,
+        load: F,
+    ) -> Result<Arc<T>, ModelError>
     where
-        T: Clone + Send + Sync + 'static,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, ModelError>>,
     {
-        println!(
-            "[QuickCache] insert into cache with key: {} and version:{} of capacity: {}",
+        if let Some(value) = self.get_ref(key, version) {
+            return Ok(value);
+        }
+
+        let loading_key = format!("{}-{}", key, version);
+        let cell = Arc::clone(
+            self.loading
+                .lock()
+                .unwrap()
+                .entry(loading_key.clone())
+                .or_default(),
+        );
+        let result = cell
+            .get_or_try_init(|| async move {
+                // Inserted by a load that finished since the lookup
+                if let Some(value) = self.peek_ref(key, version) {
+                    return Ok(value);
+                }
+                let value = Arc::new(load().await?);
+                self.insert_arc(key, version, Arc::clone(&value));
+                Ok(value)
+            })
+            .await
+            .cloned();
+
+        // Callers still waiting hold the cell, later ones read the cache
+        let mut loading = self.loading.lock().unwrap();
+        if loading
+            .get(&loading_key)
+            .is_some_and(|current| Arc::ptr_eq(current, &cell))
+        {
+            loading.remove(&loading_key);
+        }
+        result
+    }
+
+    // Insert again with the current estimate once it grew by more than 1/8, moka only weighs on insert.
+    // Age is kept, the time to live restarts
+    pub fn reweigh(&self, key: &str, version: &str) {
+        let cache_key = format!("{}-{}", key, version);
+        let Some(entry) = self.data.get(&cache_key) else {
+            return;
+        };
+        let size = entry.value.estimate_size();
+        if size <= entry.size + entry.size / 8 {
+            return;
+        }
+        info!(
+            "[QuickCache] reweigh key: {} and version:{} from {} MiB to {} MiB",
             key,
             version,
-            self.data.entry_count()
+            entry.size as u64 / MIB,
+            size as u64 / MIB
         );
+        let entry = CacheEntry {
+            value: Arc::clone(&entry.value),
+            model_id: entry.model_id.clone(),
+            version: entry.version.clone(),
+            size,
+            inserted_at: entry.inserted_at,
+        };
+        self.data.insert(cache_key, Arc::new(entry));
+    }
+
+    // True when the entry was cached
+    pub fn invalidate(&self, key: &str, version: &str) -> bool {
         let key = format!("{}-{}", key, version);
-        self.data.insert(key.to_string(), Arc::new(value.clone()));
+        let is_cached = self.data.contains_key(&key);
+        if is_cached {
+            self.data.invalidate(&key);
+            self.counters.invalidation.fetch_add(1, Ordering::Relaxed);
+        }
+        is_cached
+    }
+
+    // Sorted by model id then version
+    pub fn list_entries(&self) -> Vec<CacheEntryInfo> {
+        self.data.sync();
+        let mut entries: Vec<CacheEntryInfo> = self
+            .data
+            .iter()
+            .map(|entry| CacheEntryInfo {
+                model_id: entry.model_id.clone(),
+                version: entry.version.clone(),
+                estimated_size: entry.size,
+                age_secs: entry.inserted_at.elapsed().as_secs(),
+            })
+            .collect();
+        entries.sort_by(|a, b| (&a.model_id, &a.version).cmp(&(&b.model_id, &b.version)));
+        entries
+    }
+
+    pub fn get_stats(&self) -> CacheStats {
+        // Apply pending evictions so the counts add up
+        self.data.sync();
+        let entry_count = self.data.iter().count() as u64;
+        let insert_count = self.counters.insert.load(Ordering::Relaxed);
+        let invalidation_count = self.counters.invalidation.load(Ordering::Relaxed);
+        CacheStats {
+            entry_count,
+            estimated_size: self.data.iter().map(|entry| entry.size as u64).sum(),
+            memory_budget: self.memory_budget,
+            hit_count: self.counters.hit.load(Ordering::Relaxed),
+            miss_count: self.counters.miss.load(Ordering::Relaxed),
+            insert_count,
+            eviction_count: insert_count.saturating_sub(invalidation_count + entry_count),
+            invalidation_count,
+        }
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Blob(usize);
+
+    impl EstimateSize for Blob {
+        fn estimate_size(&self) -> usize {
+            self.0
+        }
+    }
+
+    #[test]
+    fn test_cache_config() {
+        let mut config = CacheConfig::default();
+        config
+            .apply_vars(|name| match name {
+                "CACHE_MEMORY_BUDGET_MB" => Some("512".to_string()),
+                "CACHE_TTI_SECS" => Some(" 30 ".to_string()),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(config.memory_budget_mb, 512);
+        assert_eq!(config.tti_secs, 30);
+        assert_eq!(config.ttl_secs, CacheConfig::default().ttl_secs);
+        assert!(config.apply_vars(|_| Some("1GB".to_string())).is_err());
+
+        let path = std::env::temp_dir().join(format!("cache_config_{}.json", std::process::id()));
+        std::fs::write(&path, r#"{"memory_budget_mb": 100, "ttl_secs": 60}"#).unwrap();
+        let config = CacheConfig::from_file(&path).unwrap();
+        assert_eq!((config.memory_budget_mb, config.ttl_secs), (100, 60));
+        assert_eq!(config.tti_secs, CacheConfig::default().tti_secs);
+        std::fs::write(&path, r#"{"cache_size": 2}"#).unwrap();
+        assert!(CacheConfig::from_file(&path).is_err());
+        std::fs::remove_file(path).unwrap();
+
+        let config = CacheConfig {
+            memory_budget_mb: 0,
+            ..CacheConfig::default()
+        };
+        assert!(config.validate().is_err());
+        assert!(CacheConfig::default().validate().is_ok());
+        for (ttl_secs, tti_secs) in [(0, 60), (60, 0)] {
+            let config = CacheConfig {
+                ttl_secs,
+                tti_secs,
+                ..CacheConfig::default()
+            };
+            assert!(matches!(config.validate(), Err(ModelError::InvalidInput(_))));
+        }
+    }
+
+    #[test]
+    fn test_memory_budget() {
+        let hour = Duration::from_secs(3600);
+        let cache: QuickCache<Blob> = QuickCache::with_budget(MIB, hour, hour);
+        for version in 1..=3 {
+            cache.insert_arc("m1", &version.to_string(), Arc::new(Blob(400 * 1024)));
+        }
+        cache.data.sync();
+        assert_eq!(cache.data.entry_count(), 2);
+        assert!(cache.data.weighted_size() <= MIB / WEIGHT_UNIT);
+
+        // Larger than the whole budget, not kept
+        cache.insert_arc("m2", "1", Arc::new(Blob(2 * MIB as usize)));
+        cache.data.sync();
+        assert!(cache.peek_ref("m2", "1").is_none());
+    }
+
+    struct Growing(AtomicU64);
+
+    impl EstimateSize for Growing {
+        fn estimate_size(&self) -> usize {
+            self.0.load(Ordering::Relaxed) as usize
+        }
+    }
+
+    #[test]
+    fn test_reweigh() {
+        let cache: QuickCache<Growing> = QuickCache::new(2);
+        let value = Arc::new(Growing(AtomicU64::new(800)));
+        cache.insert_arc("m1", "1", Arc::clone(&value));
+        let size = || cache.list_entries()[0].estimated_size;
+
+        // Small growth keeps the weight
+        value.0.store(900, Ordering::Relaxed);
+        cache.reweigh("m1", "1");
+        assert_eq!(size(), 800);
+
+        value.0.store(2000, Ordering::Relaxed);
+        cache.reweigh("m1", "1");
+        assert_eq!(size(), 2000);
+        assert_eq!(cache.get_stats().insert_count, 1);
+        cache.reweigh("m2", "1");
+
+        // Moka evicts against the new weight
+        let hour = Duration::from_secs(3600);
+        let cache: QuickCache<Growing> = QuickCache::with_budget(MIB, hour, hour);
+        let value = Arc::new(Growing(AtomicU64::new(300 * 1024)));
+        cache.insert_arc("m1", "1", Arc::clone(&value));
+        cache.insert_arc("m1", "2", Arc::new(Growing(AtomicU64::new(300 * 1024))));
+        value.0.store(900 * 1024, Ordering::Relaxed);
+        cache.reweigh("m1", "1");
+        cache.data.sync();
+        assert_eq!(cache.data.entry_count(), 1);
+        assert!(cache.data.weighted_size() <= MIB / WEIGHT_UNIT);
+    }
+
+    #[test]
+    fn test_entries_and_stats() {
+        let cache: QuickCache<Blob> = QuickCache::new(2);
+        assert!(cache.get_ref("m1", "1").is_none());
+        cache.insert_arc("m1", "1", Arc::new(Blob(10)));
+        cache.insert_arc("m1", "1", Arc::new(Blob(20)));
+        cache.insert_arc("m-2", "3", Arc::new(Blob(30)));
+        assert!(cache.get_ref("m1", "1").is_some());
+        assert!(cache.peek_ref("m-2", "3").is_some());
+
+        let entries = cache.list_entries();
+        let keys: Vec<(&str, &str, usize)> = entries
+            .iter()
+            .map(|e| (e.model_id.as_str(), e.version.as_str(), e.estimated_size))
+            .collect();
+        assert_eq!(keys, vec![("m-2", "3", 30), ("m1", "1", 20)]);
+
+        assert!(cache.invalidate("m1", "1"));
+        assert!(!cache.invalidate("m1", "1"));
+        let stats = cache.get_stats();
+        assert_eq!((stats.hit_count, stats.miss_count), (1, 1));
+        assert_eq!((stats.insert_count, stats.invalidation_count), (2, 1));
+        assert_eq!((stats.entry_count, stats.estimated_size), (1, 30));
+        assert_eq!(stats.eviction_count, 0);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_get_or_load_single_flight() {
+        let cache: QuickCache<Blob> = QuickCache::new(10);
+        let load_count = Arc::new(AtomicU64::new(0));
+
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let cache = cache.clone();
+                let load_count = Arc::clone(&load_count);
+                tokio::spawn(async move {
+                    cache
+                        .get_or_load("m1", "1", || async move {
+                            load_count.fetch_add(1, Ordering::Relaxed);
+                            tokio::time::sleep(Duration::from_millis(50)).await;
+                            Ok(Blob(3))
+                        })
+                        .await
+                        .unwrap()
+                })
+            })
+            .collect();
+        for handle in handles {
+            assert_eq!(handle.await.unwrap().0, 3);
+        }
+        assert_eq!(load_count.load(Ordering::Relaxed), 1);
+        assert!(cache.loading.lock().unwrap().is_empty());
+
+        // Failed load is retried by the next caller
+        let result = cache
+            .get_or_load("m2", "1", || async {
+                Err(ModelError::ModelNotFound("m2".to_string(), "1".to_string()))
+            })
+            .await;
+        assert!(result.is_err());
+        let value = cache
+            .get_or_load("m2", "1", || async { Ok(Blob(5)) })
+            .await
+            .unwrap();
+        assert_eq!(value.0, 5);
+        assert_eq!(cache.peek_ref("m2", "1").unwrap().0, 5);
+    }
+}
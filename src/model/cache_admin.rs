@@ -0,0 +1,117 @@
+use std::time::Instant;
+
+use serde::Serialize;
+
+use crate::info;
+use crate::model::{
+    app_state::{AppState, CacheEntryInfo, CacheStats, QuickCache},
+    model_error::ModelError,
+    size_estimate::EstimateSize,
+    utils::Utils,
+};
+
+// Cache inspection and admin actions shared by the MCP tools and the HTTP endpoints,
+// only exposed when CACHE_ADMIN_ENABLED is set
+
+#[derive(Debug, Serialize)]
+pub struct CacheReport {
+    pub stats: CacheStats,
+    pub entries: Vec<CacheEntryInfo>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CacheAdminStatus {
+    pub snapshot_cache: CacheReport,
+    pub centrality_cache: CacheReport,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CacheEvictResult {
+    pub model_id: String,
+    pub version: String,
+    pub is_snapshot_evicted: bool,
+    pub is_centrality_evicted: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CachePreloadResult {
+    pub model_id: String,
+    pub version: u32,
+    pub estimated_size: usize,
+    // False when the model is larger than the cache budget
+    pub is_cached: bool,
+    pub duration_ms: u128,
+}
+
+pub struct CacheAdmin;
+
+impl CacheAdmin {
+    pub fn get_status(app_state: &AppState) -> CacheAdminStatus {
+        CacheAdminStatus {
+            snapshot_cache: Self::get_report(&app_state.get_snapshot_cache()),
+            centrality_cache: Self::get_report(&app_state.get_centrality_cache()),
+        }
+    }
+
+    // Centrality of the version is evicted with its snapshot, "" or latest for the latest version
+    pub async fn evict(
+        app_state: &AppState,
+        model_id: &str,
+        version_number: &str,
+    ) -> CacheEvictResult {
+        info!(
+            "[CacheAdmin - evict] model_id: {}, version_number: {}",
+            model_id, version_number
+        );
+        let version = app_state
+            .model_parser()
+            .resolve_version(model_id, version_number)
+            .await
+            .to_string();
+        CacheEvictResult {
+            model_id: model_id.to_owned(),
+            is_snapshot_evicted: app_state
+                .get_snapshot_cache()
+                .invalidate(model_id, &version),
+            is_centrality_evicted: app_state
+                .get_centrality_cache()
+                .invalidate(model_id, &version),
+            version,
+        }
+    }
+
+    // "" or latest for the latest version
+    pub async fn preload(
+        app_state: &AppState,
+        model_id: &str,
+        version_number: &str,
+    ) -> Result<CachePreloadResult, ModelError> {
+        info!(
+            "[CacheAdmin - preload] model_id: {}, version_number: {}",
+            model_id, version_number
+        );
+        let start_time = Instant::now();
+        let model_parser = app_state.model_parser();
+        let snapshot = model_parser.preload_model(model_id, version_number).await?;
+        let version = snapshot.get_version();
+        Utils::log_time(start_time, "CacheAdmin - preload");
+
+        Ok(CachePreloadResult {
+            model_id: model_id.to_owned(),
+            version,
+            estimated_size: snapshot.estimate_size(),
+            is_cached: app_state
+                .get_snapshot_cache()
+                .peek_ref(model_id, &version.to_string())
+                .is_some(),
+            duration_ms: start_time.elapsed().as_millis(),
+        })
+    }
+
+    fn get_report<T: EstimateSize + Send + Sync + 'static>(cache: &QuickCache<T>) -> CacheReport {
+        CacheReport {
+            stats: cache.get_stats(),
+            entries: cache.list_entries(),
+        }
+    }
+}
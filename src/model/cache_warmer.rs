@@ -0,0 +1,283 @@
+use std::env;
+use std::sync::{Arc, RwLock};
+use std::time::Instant;
+
+use serde::Serialize;
+
+use crate::info;
+use crate::model::{model_error::ModelError, model_parser::ModelParser, utils::Utils};
+
+// Models loaded into the snapshot cache in the background at startup, set with
+// WARM_MODELS as comma separated model ids with an optional version, e.g.
+// WARM_MODELS=model-a,model-b:12,model-c:latest
+
+pub const LATEST: &str = "latest";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WarmTarget {
+    pub model_id: String,
+    // None for the latest version
+    pub version: Option<i32>,
+}
+
+impl WarmTarget {
+    pub fn parse_list(list: &str) -> Result<Vec<Self>, ModelError> {
+        list.split(',')
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .map(|target| {
+                let (model_id, version) = match target.split_once(':') {
+                    Some((model_id, version)) => (model_id.trim(), version.trim()),
+                    None => (target, LATEST),
+                };
+                let version = match version {
+                    LATEST | "" => None,
+                    version => Some(version.parse::<i32>().map_err(|_| {
+                        ModelError::InvalidInput(format!(
+                            "WARM_MODELS version {} of {}, expected a number or latest",
+                            version, model_id
+                        ))
+                    })?),
+                };
+                match model_id.is_empty() {
+                    true => Err(ModelError::InvalidInput(format!(
+                        "WARM_MODELS entry {} without model id",
+                        target
+                    ))),
+                    false => Ok(WarmTarget {
+                        model_id: model_id.to_owned(),
+                        version,
+                    }),
+                }
+            })
+            .collect()
+    }
+
+    pub fn from_env() -> Result<Vec<Self>, ModelError> {
+        match env::var("WARM_MODELS") {
+            Ok(list) => Self::parse_list(&list),
+            Err(_) => Ok(Vec::new()),
+        }
+    }
+
+    fn get_requested_version(&self) -> String {
+        self.version
+            .map_or(LATEST.to_string(), |version| version.to_string())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WarmState {
+    Pending,
+    Loading,
+    Loaded,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WarmEntry {
+    pub model_id: String,
+    pub requested_version: String,
+    // Resolved version once loaded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<u32>,
+    pub state: WarmState,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u128>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WarmupStatus {
+    pub is_done: bool,
+    pub total_count: usize,
+    pub loaded_count: usize,
+    pub failed_count: usize,
+    pub entries: Vec<WarmEntry>,
+}
+
+// Shared progress of the startup warming, cloned into the tools
+#[derive(Debug, Clone)]
+pub struct CacheWarmer {
+    targets: Vec<WarmTarget>,
+    entries: Arc<RwLock<Vec<WarmEntry>>>,
+}
+
+impl CacheWarmer {
+    pub fn new(targets: Vec<WarmTarget>) -> Self {
+        let entries = targets
+            .iter()
+            .map(|target| WarmEntry {
+                model_id: target.model_id.clone(),
+                requested_version: target.get_requested_version(),
+                version: None,
+                state: WarmState::Pending,
+                duration_ms: None,
+                error: None,
+            })
+            .collect();
+        Self {
+            targets,
+            entries: Arc::new(RwLock::new(entries)),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.targets.is_empty()
+    }
+
+    // One model at a time, a big model already needs most of the memory
+    pub async fn run(&self, model_parser: &ModelParser<'_>) {
+        let start_time = Instant::now();
+        let total_count = self.targets.len();
+        for (i, target) in self.targets.iter().enumerate() {
+            let requested_version = target.get_requested_version();
+            info!(
+                "[CacheWarmer - run] {}/{} loading model id {} version {}",
+                i + 1,
+                total_count,
+                target.model_id,
+                requested_version
+            );
+            self.update(i, |entry| entry.state = WarmState::Loading);
+
+            let load_start_time = Instant::now();
+            let version_number = target.version.map(|v| v.to_string()).unwrap_or_default();
+            let result = model_parser
+                .preload_model(&target.model_id, &version_number)
+                .await;
+            let duration_ms = load_start_time.elapsed().as_millis();
+            match result {
+                Ok(snapshot) => {
+                    info!(
+                        "[CacheWarmer - run] {}/{} loaded model id {} version {} in {} ms",
+                        i + 1,
+                        total_count,
+                        target.model_id,
+                        snapshot.get_version(),
+                        duration_ms
+                    );
+                    self.update(i, |entry| {
+                        entry.state = WarmState::Loaded;
+                        entry.version = Some(snapshot.get_version());
+                        entry.duration_ms = Some(duration_ms);
+                    });
+                }
+                Err(e) => {
+                    info!(
+                        "[CacheWarmer - run] {}/{} failed to load model id {} version {}: {}",
+                        i + 1,
+                        total_count,
+                        target.model_id,
+                        requested_version,
+                        e
+                    );
+                    self.update(i, |entry| {
+                        entry.state = WarmState::Failed;
+                        entry.duration_ms = Some(duration_ms);
+                        entry.error = Some(e.to_string());
+                    });
+                }
+            }
+        }
+        Utils::log_time(start_time, "CacheWarmer - run");
+    }
+
+    pub fn get_status(&self) -> WarmupStatus {
+        let entries = self.entries.read().unwrap().clone();
+        let count = |state: WarmState| entries.iter().filter(|e| e.state == state).count();
+        WarmupStatus {
+            is_done: entries
+                .iter()
+                .all(|e| matches!(e.state, WarmState::Loaded | WarmState::Failed)),
+            total_count: entries.len(),
+            loaded_count: count(WarmState::Loaded),
+            failed_count: count(WarmState::Failed),
+            entries,
+        }
+    }
+
+    fn update(&self, index: usize, apply: impl FnOnce(&mut WarmEntry)) {
+        if let Some(entry) = self.entries.write().unwrap().get_mut(index) {
+            apply(entry);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{
+        app_state::QuickCache, model_snapshot::ModelSnapshot, model_source::FileModelSource,
+    };
+
+    #[test]
+    fn test_parse_list() {
+        let targets = WarmTarget::parse_list(" model-a, model-b:12,model-c:latest,, ").unwrap();
+        assert_eq!(
+            targets,
+            vec![
+                WarmTarget {
+                    model_id: "model-a".to_string(),
+                    version: None
+                },
+                WarmTarget {
+                    model_id: "model-b".to_string(),
+                    version: Some(12)
+                },
+                WarmTarget {
+                    model_id: "model-c".to_string(),
+                    version: None
+                },
+            ]
+        );
+        assert!(WarmTarget::parse_list("model-a:first").is_err());
+        assert!(WarmTarget::parse_list(":3").is_err());
+        assert!(WarmTarget::parse_list("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_status() {
+        let warmer = CacheWarmer::new(WarmTarget::parse_list("model-a,model-b:2").unwrap());
+        let status = warmer.get_status();
+        assert!(!status.is_done);
+        assert_eq!(status.entries[0].requested_version, LATEST);
+        assert_eq!(status.entries[1].state, WarmState::Pending);
+
+        warmer.update(0, |entry| entry.state = WarmState::Loaded);
+        warmer.update(1, |entry| entry.state = WarmState::Failed);
+        let status = warmer.get_status();
+        assert!(status.is_done);
+        assert_eq!((status.loaded_count, status.failed_count), (1, 1));
+    }
+
+    #[tokio::test]
+    async fn test_run() {
+        let root = std::env::temp_dir().join(format!("cache_warmer_test_{}", std::process::id()));
+        let model_dir = root.join("m1");
+        std::fs::create_dir_all(&model_dir).unwrap();
+        std::fs::write(
+            model_dir.join("4.json"),
+            r#"{"schemaVersion": "1", "modelId": "m1", "siteModelId": "s1", "version": 4, "elements": [], "relationships": []}"#,
+        )
+        .unwrap();
+
+        let source = FileModelSource::new(root.clone());
+        let snapshot_cache: QuickCache<ModelSnapshot> = QuickCache::new(2);
+        let model_parser = ModelParser::new(snapshot_cache.clone(), QuickCache::new(2), &source);
+        let warmer = CacheWarmer::new(WarmTarget::parse_list("m1,m2:1").unwrap());
+        warmer.run(&model_parser).await;
+
+        let status = warmer.get_status();
+        assert!(status.is_done);
+        assert_eq!(status.entries[0].state, WarmState::Loaded);
+        assert_eq!(status.entries[0].version, Some(4));
+        assert_eq!(status.entries[1].state, WarmState::Failed);
+        assert!(status.entries[1].error.is_some());
+        assert!(snapshot_cache.peek_ref("m1", "4").is_some());
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
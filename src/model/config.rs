@@ -22,6 +22,7 @@ pub enum OutputToken<T> {
     Value(T),
     InArrow,
     OutArrow,
+    Relationship(T),
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -34,6 +35,8 @@ pub struct OutputGraph<T> {
     pub parent_lines: Vec<OutputLine<T>>,
     pub child_lines: Vec<OutputLine<T>>,
     pub elements_data: HashMap<String, Value>,
+    #[serde(default)]
+    pub relationships_data: HashMap<String, Value>,
 }
 
 pub enum RelationshipDirection {
@@ -41,6 +44,20 @@ pub enum RelationshipDirection {
     Child,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TraversalDirection {
+    In,
+    Out,
+    Both,
+}
+
+impl<T> Default for OutputLine<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<T> OutputLine<T> {
     pub fn new() -> Self {
         Self { line: Vec::new() }
@@ -53,6 +70,6 @@ impl<T> OutputLine<T> {
 
 impl Default for PageConfig {
     fn default() -> Self {
-        Self { elements_per_page: MAX_RESULT, page_to_get: 1 as usize }
+        Self { elements_per_page: MAX_RESULT, page_to_get: 1_usize }
     }
 }
\ No newline at end of file
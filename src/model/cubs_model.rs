@@ -1,8 +1,13 @@
 use serde::{Deserialize, Deserializer, Serialize};
 use serde_json::{Map, Value};
 use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::AtomicUsize;
 use std::{char, fmt};
 
+use crate::info;
+use crate::model::lazy_facets::LazyFacets;
+
 
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
@@ -17,8 +22,8 @@ pub struct ModelData {
     pub model_id: String,
     pub site_model_id: String,
     pub version: u32,
-    #[serde(alias = "cubsObjects", deserialize_with = "null_to_empty_vec")]
-    #[serde(alias = "cubsObjects")]
+    // Older layouts are upgraded by ModelMigration
+    #[serde(deserialize_with = "null_to_empty_vec")]
     pub elements: Vec<Element>,
     // pub elements: Value,
     #[serde(deserialize_with = "null_to_empty_vec")]
@@ -26,9 +31,27 @@ pub struct ModelData {
     // pub relationships: Value,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
 pub struct ModelVersionNumber {
     pub vers_no: i32, //postgres int4 is map back to i32
+    // Stored payload size in bytes, compressed by the source or the database
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payload_size: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schema_version: Option<String>,
+}
+
+impl ModelVersionNumber {
+    pub fn new(vers_no: i32) -> Self {
+        Self {
+            vers_no,
+            ..Default::default()
+        }
+    }
 }
 
 pub trait CusObject {
@@ -39,6 +62,92 @@ pub trait CusObject {
     fn get_dynamic_facet(&self) -> &HashMap<String, serde_json::Value>;
     fn get_facet(&self) -> &HashMap<String, serde_json::Value>;
     fn get_core_facet(&self) -> &HashMap<String, serde_json::Value>;
+    fn get_version(&self) -> u32;
+
+    // Source and target ids of objects connecting two elements
+    fn get_endpoints(&self) -> Option<(&str, &str)> {
+        None
+    }
+
+    fn get_json_value(
+        &self,
+        facet_type: &FacetType,
+        pointer: &str,
+        is_show_element_id: bool,
+    ) -> Option<Value>
+    where
+        Self: Serialize + Sized,
+    {
+        let combine_core_facet: HashMap<String, Value>;
+        let facets_map: &HashMap<String, Value> = match facet_type {
+            FacetType::CoreFacets => {
+                // Add common field into core facet for parsing
+                let mut core_facet = self.get_core_facet().clone();
+                core_facet.extend(self.get_common_fields_values_map());
+                combine_core_facet = core_facet;
+                &combine_core_facet
+            }
+            FacetType::DynamicFacets => self.get_dynamic_facet(),
+            FacetType::Facets => self.get_facet(),
+        };
+
+        // Return of no query need to be perform
+        if pointer.is_empty() || facets_map.is_empty() {
+            return match facets_map.is_empty() {
+                true => None,
+                false => self.get_filtered_value(facet_type),
+            };
+        }
+
+        //Perform json pointer
+        let mut facets_map_value = serde_json::to_value(facets_map).ok()?;
+        let v = facets_map_value.pointer_mut(pointer)?;
+        if is_show_element_id {
+            serde_json::to_value(FilteredElementResult::from(self, v.take())).ok()
+        } else {
+            Some(v.take())
+        }
+    }
+
+    // Serialized object with only the facets of facet_type
+    fn get_filtered_value(&self, facet_type: &FacetType) -> Option<Value>
+    where
+        Self: Serialize + Sized,
+    {
+        let mut value = serde_json::to_value(self).ok()?;
+        let object = value.as_object_mut()?;
+        if !matches!(facet_type, FacetType::CoreFacets) {
+            // Core facets are flattened into the object
+            for key in self.get_core_facet().keys() {
+                object.remove(key);
+            }
+        }
+        if !matches!(facet_type, FacetType::DynamicFacets) {
+            object.insert("dynamicFacets".to_string(), Value::Object(Map::new()));
+        }
+        if !matches!(facet_type, FacetType::Facets) {
+            object.insert("facets".to_string(), Value::Object(Map::new()));
+        }
+        Some(value)
+    }
+
+    fn get_common_fields_values_map(&self) -> HashMap<String, serde_json::Value> {
+        let mut fields_values_map: HashMap<String, serde_json::Value> = HashMap::new();
+
+        set_value_to_map(&mut fields_values_map, "id", &self.get_id());
+        set_value_to_map(&mut fields_values_map, "type", &self.get_type());
+        set_value_to_map(&mut fields_values_map, "nature", &self.get_nature());
+        set_value_to_map(&mut fields_values_map, "name", &self.get_name());
+
+        fields_values_map
+    }
+}
+
+fn set_value_to_map(map: &mut HashMap<String, serde_json::Value>, key: &str, fields: &str) {
+    let field_value = serde_json::to_value(fields).ok();
+    if let Some(value) = field_value {
+        map.insert(key.to_string(), value);
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -51,9 +160,11 @@ pub struct Element {
     #[serde(default)]
     pub name: String,
     pub version: u32,
+    // Parsed on first read, see SnapshotMode
     #[serde(default)]
-    pub dynamic_facets: HashMap<String, serde_json::Value>,
-    pub facets: HashMap<String, serde_json::Value>,
+    pub dynamic_facets: LazyFacets,
+    pub facets: LazyFacets,
+    // Flattened keys are buffered by serde, always parsed
     #[serde(flatten)]
     pub core_facets: HashMap<String, serde_json::Value>,
 }
@@ -94,6 +205,17 @@ impl fmt::Display for FacetType {
     }
 }
 
+impl FacetType {
+    pub fn parse(facet_type: &str) -> Option<Self> {
+        match facet_type {
+            "dynamicFacets" => Some(FacetType::DynamicFacets),
+            "coreFacets" => Some(FacetType::CoreFacets),
+            "facets" => Some(FacetType::Facets),
+            _ => None,
+        }
+    }
+}
+
 impl CusObject for Element {
     fn get_nature(&self) -> String {
         self.nature.clone()
@@ -112,97 +234,19 @@ impl CusObject for Element {
     }
 
     fn get_dynamic_facet(&self) -> &HashMap<String, serde_json::Value> {
-        &self.dynamic_facets
+        self.dynamic_facets.get()
     }
 
     fn get_facet(&self) -> &HashMap<String, serde_json::Value> {
-        &self.facets
+        self.facets.get()
     }
 
     fn get_core_facet(&self) -> &HashMap<String, serde_json::Value> {
         &self.core_facets
     }
-}
-
-impl Element {
-    pub fn get_json_value(
-        &self,
-        facet_type: &FacetType,
-        pointer: &str,
-        is_show_element_id: bool,
-    ) -> Option<Value> {
-        // Construct filtered element
-        let filtered_element = match facet_type {
-            FacetType::CoreFacets => Element {
-                dynamic_facets: HashMap::new(),
-                facets: HashMap::new(),
-                ..self.clone()
-            },
-            FacetType::DynamicFacets => Element {
-                core_facets: HashMap::new(),
-                facets: HashMap::new(),
-                ..self.clone()
-            },
-            FacetType::Facets => Element {
-                core_facets: HashMap::new(),
-                dynamic_facets: HashMap::new(),
-                ..self.clone()
-            },
-        };
-
-        let mut combine_core_facet = filtered_element.core_facets.clone();
-        let facets_map: &HashMap<String, Value> = match facet_type {
-            FacetType::CoreFacets => {
-                // Add common field into core facet for parsing
-                let common_fields: HashMap<String, Value> = self.get_common_fields_values_map();
-                combine_core_facet.extend(common_fields);
-                &combine_core_facet
-            }
-            FacetType::DynamicFacets => &filtered_element.dynamic_facets,
-            FacetType::Facets => &filtered_element.facets,
-        };
-
-        // Return of no query need to be perform
-        if pointer.is_empty() || facets_map.is_empty() {
-            return match facets_map.is_empty() {
-                true => None,
-                false => serde_json::to_value(&filtered_element).ok(),
-            };
-        }
-
-        //Perform json pointer
-        let mut facets_map_value = serde_json::to_value(facets_map).unwrap();
-        let ptr = facets_map_value.pointer_mut(pointer);
-        match ptr {
-            Some(v) => {
-                let result = if is_show_element_id {
-                    let e = FilteredElementResult::from(&filtered_element, v.take());
-                    serde_json::to_value(e).ok()
-                } else {
-                    Some(v.take())
-                };
-                result
-            }
-            None => None,
-        }
-    }
-
-    pub fn get_common_fields_values_map(&self) -> HashMap<String, serde_json::Value> {
-        let mut fields_values_map: HashMap<String, serde_json::Value> = HashMap::new();
-
-        Self::set_value_to_map(& mut fields_values_map, "id", &self.id.clone());
-        Self::set_value_to_map(& mut fields_values_map, "type", &self.type_.clone());
-        Self::set_value_to_map(& mut fields_values_map, "nature", &self.nature.clone());
-        Self::set_value_to_map(& mut fields_values_map, "name", &self.name.clone());
-
-        fields_values_map
-    }
 
-    fn set_value_to_map(map: &mut HashMap<String, serde_json::Value>, key: &str, fields: &str) {
-        let field_value = serde_json::to_value(fields).ok();
-        if let Some(value) = field_value {
-            map.insert(key.to_string(), value);
-        }
+    fn get_version(&self) -> u32 {
+        self.version
     }
 }
 
@@ -234,6 +278,14 @@ impl CusObject for Relationship {
     fn get_core_facet(&self) -> &HashMap<String, serde_json::Value> {
         &self.core_facets
     }
+
+    fn get_version(&self) -> u32 {
+        self.version
+    }
+
+    fn get_endpoints(&self) -> Option<(&str, &str)> {
+        Some((&self.source_id, &self.target_id))
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -246,18 +298,46 @@ pub struct FilteredElementResult {
     #[serde(default)]
     pub name: String,
     pub version: u32,
+    // Relationship only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_id: Option<String>,
     pub filtered_result: Value,
 }
 
 impl FilteredElementResult {
-    pub fn from(element: &Element, result: Value) -> FilteredElementResult {
+    pub fn from<T: CusObject>(object: &T, result: Value) -> FilteredElementResult {
+        let endpoints = object.get_endpoints();
         FilteredElementResult {
+            id: object.get_id(),
+            type_: object.get_type(),
+            nature: object.get_nature(),
+            name: object.get_name(),
+            version: object.get_version(),
+            source_id: endpoints.map(|(source_id, _)| source_id.to_owned()),
+            target_id: endpoints.map(|(_, target_id)| target_id.to_owned()),
+            filtered_result: result,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ElementSummary {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub type_: String,
+    pub nature: String,
+    pub name: String,
+}
+
+impl ElementSummary {
+    pub fn from(element: &Element) -> ElementSummary {
+        ElementSummary {
             id: element.id.clone(),
             type_: element.type_.clone(),
             nature: element.nature.clone(),
             name: element.name.clone(),
-            version: element.version,
-            filtered_result: result,
         }
     }
 }
@@ -274,6 +354,22 @@ where
 
 //TODO refactor using trait
 impl ModelData {
+    // Parse every element facet map now and free the raw json
+    pub fn parse_facets(&mut self) {
+        for element in self.elements.iter_mut() {
+            element.dynamic_facets.parse();
+            element.facets.parse();
+        }
+    }
+
+    // Element facet maps parsed on read add their size to parsed_size
+    pub fn track_parsed_size(&mut self, parsed_size: &Arc<AtomicUsize>) {
+        for element in self.elements.iter_mut() {
+            element.dynamic_facets.track_parsed_size(parsed_size);
+            element.facets.track_parsed_size(parsed_size);
+        }
+    }
+
     pub fn get_elements(&self) -> Vec<&Element> {
         self.elements.iter().collect()
     }
@@ -291,19 +387,20 @@ impl ModelData {
         self.elements.iter().filter(|e| filter(e)).collect()
     }
 
-    pub fn get_json_values(
-        elements: Vec<&Element>,
+    // Works on elements and relationships
+    pub fn get_json_values<T: CusObject + Serialize>(
+        objects: Vec<&T>,
         facet_type: Option<FacetType>,
         pointer: &str,
         is_show_element_id: bool,
     ) -> Vec<Value> {
-        println!(
+        info!(
             "[Element - get_json_values] for {:?} with path {}",
             facet_type, pointer
         );
 
         if let Some(facet_type) = facet_type {
-            elements
+            objects
                 .iter()
                 .filter_map(|e| e.get_json_value(&facet_type, pointer, is_show_element_id))
                 .collect()
@@ -314,12 +411,10 @@ impl ModelData {
 }
 
 pub fn truncate_value(values: &[Value], truncate_depth: usize) -> Vec<Value> {
-    let result = values
+    values
         .iter()
         .map(|v| truncate(v, truncate_depth, 0))
-        .collect();
-
-    result
+        .collect()
 }
 
 fn truncate(value: &Value, max_depth: usize, current_depth: usize) -> Value {
@@ -354,7 +449,6 @@ fn truncate(value: &Value, max_depth: usize, current_depth: usize) -> Value {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use serde_json::json;
 
     #[test]
     fn test_truncate() {
@@ -375,4 +469,35 @@ mod tests {
         let result_string = result.to_string();
         assert_eq!(result_string, r#"{"a":1,"b":{"c":null}}"#);
     }
+
+    #[test]
+    fn test_relationship_json_value() {
+        let relationship: Relationship = serde_json::from_value(serde_json::json!({
+            "id": "r1", "sourceId": "p1", "targetId": "t1", "type": "feeds", "nature": "Flow",
+            "version": 1, "facets": {"flowRate": {"value": 12.5}}, "material": "steel"
+        }))
+        .unwrap();
+
+        let value = relationship.get_json_value(&FacetType::Facets, "/flowRate/value", true);
+        let result: FilteredElementResult = serde_json::from_value(value.unwrap()).unwrap();
+        assert_eq!(result.source_id.as_deref(), Some("p1"));
+        assert_eq!(result.target_id.as_deref(), Some("t1"));
+        assert_eq!(result.filtered_result, serde_json::json!(12.5));
+
+        // Core facets include the common fields, other facets are emptied
+        let value = relationship
+            .get_json_value(&FacetType::CoreFacets, "", false)
+            .unwrap();
+        assert_eq!(value["material"], "steel");
+        assert_eq!(value["facets"], serde_json::json!({}));
+        let value = relationship
+            .get_json_value(&FacetType::Facets, "", false)
+            .unwrap();
+        assert!(value.get("material").is_none());
+        assert!(
+            relationship
+                .get_json_value(&FacetType::DynamicFacets, "", false)
+                .is_none()
+        );
+    }
 }
@@ -2,6 +2,8 @@
 use sqlx::postgres::PgPoolOptions;
 use std::{env, time::Duration};
 
+use crate::info;
+
 /* DB */
 pub async fn connect_to_db() -> sqlx::Pool<sqlx::Postgres> {
 
@@ -15,6 +17,6 @@ pub async fn connect_to_db() -> sqlx::Pool<sqlx::Postgres> {
         .await
         .expect("Failed to create DB pool.");
 
-    println!("Connected to the database");
+    info!("Connected to the database");
     pg_pool
 }
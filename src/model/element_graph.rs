@@ -1,149 +1,432 @@
 use std::collections::HashMap;
+use std::mem::size_of;
+use std::sync::{Arc, OnceLock};
+
+use crate::model::{
+    cubs_model::Relationship,
+    size_estimate::{EstimateSize, hash_map_size},
+};
 
 // Graph hold all the connection
+// Element and relationship ids are interned to u32 index, adjacency is stored CSR style
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct ElementGraph {
-    // Each element contain one connector
-    connectors: HashMap<String, ElementConnector>,
-    connected_relationship: Vec<String>,
+    // Interned element id, the position is the node index
+    elements: Interner,
+    // Node added with add_connector, other nodes only exist as a relationship end
+    is_connector: Vec<bool>,
+    connector_count: usize,
+    edges: Vec<Edge>,
+    // Relationship id : edge index
+    edge_index: HashMap<Arc<str>, u32>,
+    // Interned relationship type and nature
+    labels: Interner,
+    // Relationship with both ends added as connector, shares the edge id
+    connected_relationship: Vec<Arc<str>>,
+    // Built on first traversal, reset when the graph change
+    adjacency: OnceLock<Adjacency>,
 }
 
-#[derive(Clone, Debug)]
-pub struct ElementConnector {
-    element_id: String,
-    in_ids: Vec<Path>,
-    out_ids: Vec<Path>,
+#[derive(Clone, Debug, Default)]
+struct Interner {
+    values: Vec<Arc<str>>,
+    index: HashMap<Arc<str>, u32>,
 }
 
 #[derive(Clone, Debug)]
-pub struct Path(pub String, pub String); //Relationship id : id
+struct Edge {
+    id: Arc<str>,
+    name: Box<str>,
+    source: u32,
+    target: u32,
+    type_: u32,
+    nature: u32,
+}
+
+// Edge index of node i are edges[offsets[i]..offsets[i + 1]]
+#[derive(Clone, Debug, Default)]
+struct Adjacency {
+    out_offsets: Vec<u32>,
+    out_edges: Vec<u32>,
+    in_offsets: Vec<u32>,
+    in_edges: Vec<u32>,
+}
+
+// View of an element and its relationships
+#[derive(Clone, Copy)]
+pub struct ElementConnector<'a> {
+    graph: &'a ElementGraph,
+    node: u32,
+    in_filter: PathFilter,
+    out_filter: PathFilter,
+}
+
+// Paths of a connector view, narrowed with clear_*_id and retain_*_id
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum PathFilter {
+    All,
+    Element(u32),
+    Nothing,
+}
+
+// View of a relationship seen from one of its end
+#[derive(Clone, Copy)]
+pub struct Path<'a> {
+    graph: &'a ElementGraph,
+    edge: u32,
+    // Element at the other end
+    node: u32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RelationshipEdge<'a> {
+    pub id: &'a str,
+    pub source_id: &'a str,
+    pub target_id: &'a str,
+    pub type_: &'a str,
+    pub nature: &'a str,
+    pub name: &'a str,
+}
 
 //Implementation
 impl ElementGraph {
     pub fn new() -> Self {
-        Self {
-            connectors: HashMap::new(),
-            connected_relationship: Vec::new(),
-        }
+        Self::default()
     }
 
     // Add connector without connection
     pub fn add_connector(&mut self, id: &str) {
-        if !self.connectors.contains_key(id) {
-            self.connectors.insert(
-                id.to_owned(),
-                ElementConnector {
-                    element_id: id.to_owned(),
-                    in_ids: Vec::new(),
-                    out_ids: Vec::new(),
-                },
-            );
+        let node = self.intern_element(id) as usize;
+        if !self.is_connector[node] {
+            self.is_connector[node] = true;
+            self.connector_count += 1;
+            self.adjacency = OnceLock::new();
         }
     }
 
-    pub fn push_connector(&mut self, id: &str, connector: ElementConnector) {
-        self.connectors.insert(id.to_owned(), connector);
-    }
-
     // Connect element
     pub fn connect(&mut self, relationship_id: &str, from_id: &str, to_id: &str) {
-        let mut connected_in = false;
-        let mut connected_out = false;
+        self.connect_edge(RelationshipEdge {
+            id: relationship_id,
+            source_id: from_id,
+            target_id: to_id,
+            type_: "",
+            nature: "",
+            name: "",
+        });
+    }
+
+    // Connect element keeping the relationship type, nature and name on the edge
+    pub fn connect_relationship(&mut self, relationship: &Relationship) {
+        self.connect_edge(RelationshipEdge {
+            id: &relationship.id,
+            source_id: &relationship.source_id,
+            target_id: &relationship.target_id,
+            type_: &relationship.type_,
+            nature: &relationship.nature,
+            name: &relationship.name,
+        });
+    }
+
+    // Connect element with a path of another graph
+    pub fn connect_path(&mut self, path: &Path) {
+        self.connect_edge(path.get_relationship());
+    }
 
-        // From Obj --> add output
-        if let Some(from_obj) = self.connectors.get_mut(from_id) {
-            from_obj
-                .out_ids
-                .push(Path(relationship_id.to_owned(), to_id.to_owned()));
-            connected_in = true;
+    // Add connector of another graph with the paths kept on it
+    pub fn push_connector(&mut self, id: &str, connector: ElementConnector) {
+        self.add_connector(id);
+        for path in connector.get_in_id().iter().chain(connector.get_out_id().iter()) {
+            if !self.edge_index.contains_key(path.get_relationship_id()) {
+                self.connect_path(path);
+            }
         }
+    }
+
+    pub fn add_connected_relationship(&mut self, relationship_id: &str) {
+        let id = self
+            .edge_index
+            .get_key_value(relationship_id)
+            .map_or_else(|| Arc::from(relationship_id), |(id, _)| Arc::clone(id));
+        self.connected_relationship.push(id);
+    }
+
+    fn connect_edge(&mut self, edge: RelationshipEdge) {
+        let source = self.intern_element(edge.source_id);
+        let target = self.intern_element(edge.target_id);
+        let index = self.edges.len() as u32;
 
-        // To Obj --> add input
-        if let Some(to_obj) = self.connectors.get_mut(to_id) {
-            to_obj
-                .in_ids
-                .push(Path(relationship_id.to_owned(), from_id.to_owned()));
-            connected_out = true;
+        let id: Arc<str> = Arc::from(edge.id);
+        if self.is_connector[source as usize] && self.is_connector[target as usize] {
+            self.connected_relationship.push(Arc::clone(&id));
         }
 
-        if connected_in && connected_out {
-            self.connected_relationship.push(relationship_id.to_owned());
+        self.edge_index.insert(Arc::clone(&id), index);
+        self.edges.push(Edge {
+            id,
+            name: Box::from(edge.name),
+            source,
+            target,
+            type_: self.labels.intern(edge.type_),
+            nature: self.labels.intern(edge.nature),
+        });
+        self.adjacency = OnceLock::new();
+    }
+
+    fn intern_element(&mut self, id: &str) -> u32 {
+        let node = self.elements.intern(id);
+        if node as usize == self.is_connector.len() {
+            self.is_connector.push(false);
         }
+        node
+    }
+
+    fn adjacency(&self) -> &Adjacency {
+        self.adjacency
+            .get_or_init(|| Adjacency::build(self.elements.len(), &self.edges))
+    }
+
+    pub fn get_connection(&self, id: &str) -> Option<ElementConnector<'_>> {
+        self.elements
+            .get(id)
+            .filter(|node| self.is_connector[*node as usize])
+            .map(|node| ElementConnector {
+                graph: self,
+                node,
+                in_filter: PathFilter::All,
+                out_filter: PathFilter::All,
+            })
+    }
+
+    pub fn get_relationship(&self, relationship_id: &str) -> Option<RelationshipEdge<'_>> {
+        self.edge_index
+            .get(relationship_id)
+            .map(|edge| self.relationship_edge(*edge))
     }
 
-    pub fn get_connection(&self, id: &str) -> Option<&ElementConnector> {
-        self.connectors.get(id)
+    fn relationship_edge(&self, edge: u32) -> RelationshipEdge<'_> {
+        let edge = &self.edges[edge as usize];
+        RelationshipEdge {
+            id: &edge.id,
+            source_id: self.elements.resolve(edge.source),
+            target_id: self.elements.resolve(edge.target),
+            type_: self.labels.resolve(edge.type_),
+            nature: self.labels.resolve(edge.nature),
+            name: &edge.name,
+        }
     }
 
     pub fn get_connection_count(&self) -> usize {
-        self.connectors.len()
+        self.connector_count
     }
 
     pub fn get_connected_relationship_count(&self) -> usize {
         self.connected_relationship.len()
     }
 
-    pub fn add_connected_relationship(&mut self, relationship_id: &str) {
-        self.connected_relationship.push(relationship_id.to_owned());
-    }
-
     pub fn get_connected_relationship(&self) -> Vec<String> {
-        self.connected_relationship.clone()
+        self.connected_relationship
+            .iter()
+            .map(|id| id.to_string())
+            .collect()
     }
 
     pub fn get_all_elements(&self) -> Vec<String> {
-        self.connectors.keys().map(|k| k.clone()).collect()
+        self.elements
+            .values
+            .iter()
+            .zip(self.is_connector.iter())
+            .filter(|(_, is_connector)| **is_connector)
+            .map(|(id, _)| id.to_string())
+            .collect()
+    }
+}
+
+impl Interner {
+    fn intern(&mut self, value: &str) -> u32 {
+        if let Some(index) = self.index.get(value) {
+            return *index;
+        }
+        let index = self.values.len() as u32;
+        let value: Arc<str> = Arc::from(value);
+        self.index.insert(Arc::clone(&value), index);
+        self.values.push(value);
+        index
+    }
+
+    fn get(&self, value: &str) -> Option<u32> {
+        self.index.get(value).copied()
+    }
+
+    fn resolve(&self, index: u32) -> &str {
+        &self.values[index as usize]
+    }
+
+    fn len(&self) -> usize {
+        self.values.len()
+    }
+}
+
+impl Adjacency {
+    fn build(node_count: usize, edges: &[Edge]) -> Self {
+        let (out_offsets, out_edges) = Self::build_csr(node_count, edges, |e| e.source);
+        let (in_offsets, in_edges) = Self::build_csr(node_count, edges, |e| e.target);
+        Self {
+            out_offsets,
+            out_edges,
+            in_offsets,
+            in_edges,
+        }
+    }
+
+    // Counting sort of the edges by node, keeping the connection order
+    fn build_csr<F>(node_count: usize, edges: &[Edge], node_of: F) -> (Vec<u32>, Vec<u32>)
+    where
+        F: Fn(&Edge) -> u32,
+    {
+        let mut offsets = vec![0_u32; node_count + 1];
+        for edge in edges {
+            offsets[node_of(edge) as usize + 1] += 1;
+        }
+        for i in 0..node_count {
+            offsets[i + 1] += offsets[i];
+        }
+
+        let mut next = offsets.clone();
+        let mut adjacent = vec![0_u32; edges.len()];
+        for (index, edge) in edges.iter().enumerate() {
+            let node = node_of(edge) as usize;
+            adjacent[next[node] as usize] = index as u32;
+            next[node] += 1;
+        }
+        (offsets, adjacent)
+    }
+
+    fn out_edges(&self, node: u32) -> &[u32] {
+        let node = node as usize;
+        &self.out_edges[self.out_offsets[node] as usize..self.out_offsets[node + 1] as usize]
+    }
+
+    fn in_edges(&self, node: u32) -> &[u32] {
+        let node = node as usize;
+        &self.in_edges[self.in_offsets[node] as usize..self.in_offsets[node + 1] as usize]
+    }
+}
+
+impl PathFilter {
+    // Unknown element keeps nothing
+    fn retain(self, node: Option<u32>) -> Self {
+        match (self, node) {
+            (PathFilter::All, Some(node)) => PathFilter::Element(node),
+            (PathFilter::Element(kept), Some(node)) if kept == node => self,
+            _ => PathFilter::Nothing,
+        }
+    }
+
+    fn is_kept(self, node: u32) -> bool {
+        match self {
+            PathFilter::All => true,
+            PathFilter::Element(kept) => kept == node,
+            PathFilter::Nothing => false,
+        }
+    }
+}
+
+impl<'a> Path<'a> {
+    pub fn get_relationship_id(&self) -> &'a str {
+        &self.graph.edges[self.edge as usize].id
+    }
+
+    pub fn get_element_id(&self) -> &'a str {
+        self.graph.elements.resolve(self.node)
+    }
+
+    pub fn get_relationship(&self) -> RelationshipEdge<'a> {
+        self.graph.relationship_edge(self.edge)
     }
 }
 
-impl ElementConnector {
-    pub fn get_element_id(&self) -> &str {
-        &self.element_id
+impl<'a> ElementConnector<'a> {
+    pub fn get_element_id(&self) -> &'a str {
+        self.graph.elements.resolve(self.node)
     }
 
-    pub fn get_in_id(&self) -> Vec<&Path> {
-        self.in_ids.iter().map(|id| id).collect()
+    pub fn get_in_id(&self) -> Vec<Path<'a>> {
+        let graph = self.graph;
+        graph
+            .adjacency()
+            .in_edges(self.node)
+            .iter()
+            .map(|edge| Path {
+                graph,
+                edge: *edge,
+                node: graph.edges[*edge as usize].source,
+            })
+            .filter(|path| self.in_filter.is_kept(path.node))
+            .collect()
     }
 
-    pub fn get_out_id(&self) -> Vec<&Path> {
-        self.out_ids.iter().map(|id| id).collect()
+    pub fn get_out_id(&self) -> Vec<Path<'a>> {
+        let graph = self.graph;
+        graph
+            .adjacency()
+            .out_edges(self.node)
+            .iter()
+            .map(|edge| Path {
+                graph,
+                edge: *edge,
+                node: graph.edges[*edge as usize].target,
+            })
+            .filter(|path| self.out_filter.is_kept(path.node))
+            .collect()
     }
 
+    // Only narrow this view, the graph is unchanged
     pub fn clear_in_id(&mut self) {
-        self.in_ids.clear();
+        self.in_filter = PathFilter::Nothing;
     }
 
     pub fn clear_out_id(&mut self) {
-        self.out_ids.clear();
+        self.out_filter = PathFilter::Nothing;
     }
 
     pub fn retain_in_id(&mut self, id: &str) {
-        self.in_ids.retain(|p| p.1 == id);
+        self.in_filter = self.in_filter.retain(self.graph.elements.get(id));
     }
 
     pub fn retain_out_id(&mut self, id: &str) {
-        self.out_ids.retain(|p| p.1 == id);
+        self.out_filter = self.out_filter.retain(self.graph.elements.get(id));
     }
 
     pub fn is_in_ids_empty(&self) -> bool {
-        self.in_ids.is_empty()
+        let graph = self.graph;
+        !graph
+            .adjacency()
+            .in_edges(self.node)
+            .iter()
+            .any(|edge| self.in_filter.is_kept(graph.edges[*edge as usize].source))
     }
 
     pub fn is_out_ids_empty(&self) -> bool {
-        self.out_ids.is_empty()
+        let graph = self.graph;
+        !graph
+            .adjacency()
+            .out_edges(self.node)
+            .iter()
+            .any(|edge| self.out_filter.is_kept(graph.edges[*edge as usize].target))
     }
 }
 
 // Trait
-impl<'a> std::fmt::Display for ElementConnector {
+impl std::fmt::Display for ElementConnector<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let id = &self.element_id;
+        let id = self.get_element_id();
 
         //Parent
-        for path in &self.in_ids {
-            let id = &path.1;
-            let rel_id = &path.0;
+        for path in self.get_in_id() {
+            let id = path.get_element_id();
+            let rel_id = path.get_relationship_id();
             writeln!(f, "<{}> -- ({}) --> ", id, rel_id)?;
         }
 
@@ -151,9 +434,9 @@ impl<'a> std::fmt::Display for ElementConnector {
         writeln!(f, "              [{}] ", id)?;
 
         // Child
-        for path in &self.out_ids {
-            let id = &path.1;
-            let rel_id = &path.0;
+        for path in self.get_out_id() {
+            let id = path.get_element_id();
+            let rel_id = path.get_relationship_id();
             writeln!(f, "                 -- ({}) --> <{}>", rel_id, id)?;
         }
 
@@ -161,9 +444,64 @@ impl<'a> std::fmt::Display for ElementConnector {
     }
 }
 
+impl std::fmt::Debug for ElementConnector<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ElementConnector")
+            .field("element_id", &self.get_element_id())
+            .finish()
+    }
+}
+
+impl std::fmt::Debug for Path<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Path")
+            .field(&self.get_relationship_id())
+            .field(&self.get_element_id())
+            .finish()
+    }
+}
+
+// Arc<str> values are counted once, where they are first stored
+fn map_entries_size<K, V>(map: &HashMap<K, V>) -> usize {
+    hash_map_size(map.len(), size_of::<(K, V)>())
+}
+
+impl EstimateSize for Interner {
+    fn estimate_size(&self) -> usize {
+        self.values
+            .iter()
+            .map(|v| size_of::<Arc<str>>() + 2 * size_of::<usize>() + v.len())
+            .sum::<usize>()
+            + map_entries_size(&self.index)
+    }
+}
+
+impl EstimateSize for ElementGraph {
+    fn estimate_size(&self) -> usize {
+        let edges: usize = self
+            .edges
+            .iter()
+            .map(|e| size_of::<Edge>() + 2 * size_of::<usize>() + e.id.len() + e.name.len())
+            .sum();
+        let adjacency = self.adjacency.get().map_or(0, |a| {
+            size_of::<u32>()
+                * (a.out_offsets.len() + a.out_edges.len() + a.in_offsets.len() + a.in_edges.len())
+        });
+        size_of::<ElementGraph>()
+            + self.elements.estimate_size()
+            + self.is_connector.len()
+            + edges
+            + map_entries_size(&self.edge_index)
+            + self.labels.estimate_size()
+            + size_of::<Arc<str>>() * self.connected_relationship.len()
+            + adjacency
+    }
+}
+
 #[cfg(test)]
+#[allow(clippy::assertions_on_constants)]
 mod tests {
-    use crate::model::element_graph::{ElementConnector, ElementGraph};
+    use crate::model::{cubs_model::Relationship, element_graph::ElementGraph};
 
     #[test]
     fn test_new() {
@@ -247,4 +585,60 @@ mod tests {
 
         assert!(true);
     }
+
+    #[test]
+    fn test_relationship_edge() {
+        let mut graph = ElementGraph::new();
+        graph.add_connector("c1");
+        graph.add_connector("c2");
+        let relationship: Relationship = serde_json::from_value(serde_json::json!({
+            "id": "r1", "sourceId": "c1", "targetId": "c2", "type": "feeds",
+            "nature": "flow", "name": "Feed line", "version": 1, "facets": {}
+        }))
+        .unwrap();
+        graph.connect_relationship(&relationship);
+
+        let edge = graph.get_relationship("r1").unwrap();
+        assert_eq!(edge.type_, "feeds");
+        assert_eq!(edge.nature, "flow");
+        assert_eq!(edge.name, "Feed line");
+
+        // Both sides share the same edge label
+        let out_path = &graph.get_connection("c1").unwrap().get_out_id()[0];
+        let in_path = &graph.get_connection("c2").unwrap().get_in_id()[0];
+        assert_eq!(out_path.get_relationship(), edge);
+        assert_eq!(in_path.get_relationship().type_, "feeds");
+        assert_eq!(in_path.get_element_id(), "c1");
+        assert!(graph.get_relationship("unknown").is_none());
+    }
+
+    #[test]
+    fn test_push_narrowed_connector() {
+        let mut graph = ElementGraph::new();
+        for id in ["c1", "c2", "c3", "c4"] {
+            graph.add_connector(id);
+        }
+        graph.connect("r1", "c1", "c3");
+        graph.connect("r2", "c2", "c3");
+        graph.connect("r3", "c3", "c4");
+
+        let mut connector = graph.get_connection("c3").unwrap();
+        connector.retain_in_id("c1");
+        connector.clear_out_id();
+        assert_eq!(connector.get_in_id().len(), 1);
+        assert!(connector.is_out_ids_empty());
+        // The source graph is unchanged
+        assert_eq!(graph.get_connection("c3").unwrap().get_out_id().len(), 1);
+
+        let mut partial_graph = ElementGraph::new();
+        partial_graph.push_connector("c3", connector);
+        partial_graph.add_connected_relationship("r1");
+        assert_eq!(partial_graph.get_connection_count(), 1);
+        assert!(partial_graph.get_relationship("r1").is_some());
+        assert!(partial_graph.get_relationship("r2").is_none());
+        assert_eq!(partial_graph.get_connected_relationship(), vec!["r1"]);
+
+        connector.retain_in_id("c2");
+        assert!(connector.is_in_ids_empty());
+    }
 }
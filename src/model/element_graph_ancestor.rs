@@ -0,0 +1,217 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+
+use crate::model::{
+    element_graph::{ElementGraph, Path},
+    model_error::ModelError,
+    utils::Utils,
+};
+
+// Shared ancestors of several elements following the in_ids side of the ElementGraph.
+// An input element counts as its own ancestor at distance 0, so an input that is
+// above the other inputs is returned as their common ancestor.
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AncestorDistance {
+    pub element_id: String,
+    pub distance: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommonAncestor {
+    pub ancestor_id: String,
+    // True when no other common ancestor is below this one
+    pub is_lowest: bool,
+    pub distances: Vec<AncestorDistance>,
+}
+
+pub struct ElementGraphAncestor;
+
+impl ElementGraphAncestor {
+    pub fn common_ancestors<F>(
+        graph: &ElementGraph,
+        element_ids: &[String],
+        is_relationship_selected: F,
+    ) -> Result<Vec<CommonAncestor>, ModelError>
+    where
+        F: Fn(&Path) -> bool,
+    {
+        if element_ids.len() < 2 {
+            return Err(ModelError::InvalidInput(
+                "At least two element ids are required to find common ancestors".to_string(),
+            ));
+        }
+        let start_time = Instant::now();
+
+        // Distance from every input to each of its ancestors (itself included)
+        let mut ancestor_distances: Vec<HashMap<String, usize>> = Vec::new();
+        for id in element_ids {
+            if graph.get_connection(id).is_none() {
+                return Err(ModelError::ParsingError(format!("Element: {}", id)));
+            }
+            ancestor_distances.push(Self::ancestors(graph, id, &is_relationship_selected));
+        }
+
+        // Intersection
+        let (first, others) = ancestor_distances.split_first().unwrap();
+        let common: HashSet<&String> = first
+            .keys()
+            .filter(|a| others.iter().all(|d| d.contains_key(*a)))
+            .collect();
+
+        // An ancestor is not lowest if another common ancestor descends from it,
+        // one upward pass from the parents of every common ancestor
+        let not_lowest = Self::strict_ancestors(graph, &common, &is_relationship_selected);
+
+        let mut result: Vec<CommonAncestor> = common
+            .iter()
+            .map(|ancestor| CommonAncestor {
+                ancestor_id: ancestor.to_string(),
+                is_lowest: !not_lowest.contains(ancestor.as_str()),
+                distances: element_ids
+                    .iter()
+                    .zip(ancestor_distances.iter())
+                    .map(|(id, distances)| AncestorDistance {
+                        element_id: id.clone(),
+                        distance: distances[*ancestor],
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        // Lowest first, then closest
+        result.sort_by_key(|a| {
+            (
+                !a.is_lowest,
+                a.distances.iter().map(|d| d.distance).max().unwrap_or(0),
+                a.ancestor_id.clone(),
+            )
+        });
+
+        Utils::log_time(start_time, "ElementGraphAncestor - common_ancestors");
+        Ok(result)
+    }
+
+    // Elements above any of the given ones, self loops left out
+    fn strict_ancestors<'g, F>(
+        graph: &'g ElementGraph,
+        element_ids: &HashSet<&String>,
+        is_relationship_selected: &F,
+    ) -> HashSet<&'g str>
+    where
+        F: Fn(&Path) -> bool,
+    {
+        // Inputs are only marked once reached from another one
+        let mut visited: HashSet<&str> = HashSet::new();
+        let mut queue: VecDeque<&str> = element_ids.iter().map(|id| id.as_str()).collect();
+        while let Some(id) = queue.pop_front() {
+            let Some(connector) = graph.get_connection(id) else {
+                continue;
+            };
+            for path in connector.get_in_id() {
+                let parent_id = path.get_element_id();
+                if is_relationship_selected(&path) && parent_id != id && visited.insert(parent_id) {
+                    queue.push_back(parent_id);
+                }
+            }
+        }
+
+        visited
+    }
+
+    // BFS over in_ids returning the shortest distance to every ancestor
+    fn ancestors<F>(
+        graph: &ElementGraph,
+        element_id: &str,
+        is_relationship_selected: &F,
+    ) -> HashMap<String, usize>
+    where
+        F: Fn(&Path) -> bool,
+    {
+        let mut distances: HashMap<String, usize> = HashMap::new();
+        let mut queue: VecDeque<(&str, usize)> = VecDeque::new();
+        distances.insert(element_id.to_owned(), 0);
+        queue.push_back((element_id, 0));
+
+        while let Some((id, distance)) = queue.pop_front() {
+            let Some(connector) = graph.get_connection(id) else {
+                continue;
+            };
+            for path in connector.get_in_id() {
+                if !is_relationship_selected(&path) || distances.contains_key(path.get_element_id())
+                {
+                    continue;
+                }
+                distances.insert(path.get_element_id().to_owned(), distance + 1);
+                queue.push_back((path.get_element_id(), distance + 1));
+            }
+        }
+
+        distances
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::test_fixture;
+
+    // site -> area -> (pump, tank), site -> tank2
+    const IDS: [&str; 5] = ["site", "area", "pump", "tank", "tank2"];
+    const RELATIONSHIPS: [(&str, &str, &str); 5] = [
+        ("r1", "site", "area"),
+        ("r2", "area", "pump"),
+        ("r3", "area", "tank"),
+        ("r4", "site", "tank2"),
+        ("r5", "pump", "tank"),
+    ];
+
+    #[test]
+    fn test_common_ancestors() {
+        let graph = test_fixture::build_graph(&IDS, &RELATIONSHIPS);
+        let ids = vec!["pump".to_string(), "tank".to_string()];
+        let result = ElementGraphAncestor::common_ancestors(&graph, &ids, |_| true).unwrap();
+
+        // pump is above tank so it is their lowest common ancestor
+        let ancestors: Vec<&str> = result.iter().map(|a| a.ancestor_id.as_str()).collect();
+        assert_eq!(ancestors, vec!["pump", "area", "site"]);
+        assert!(result[0].is_lowest);
+        assert!(!result[1].is_lowest);
+        assert_eq!(result[1].distances[0].distance, 1);
+        assert_eq!(result[1].distances[1].distance, 1);
+        assert_eq!(result[2].distances[0].distance, 2);
+    }
+
+    #[test]
+    fn test_common_ancestors_with_relationship_filter() {
+        let graph = test_fixture::build_graph(&IDS, &RELATIONSHIPS);
+        let ids = vec!["pump".to_string(), "tank".to_string()];
+        let result = ElementGraphAncestor::common_ancestors(&graph, &ids, |p| {
+            p.get_relationship_id() != "r5"
+        })
+        .unwrap();
+
+        assert_eq!(result[0].ancestor_id, "area");
+        assert!(result[0].is_lowest);
+
+        let ids = vec!["tank".to_string(), "tank2".to_string()];
+        let result = ElementGraphAncestor::common_ancestors(&graph, &ids, |_| true).unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].ancestor_id, "site");
+        assert_eq!(result[0].distances[0].distance, 2);
+        assert_eq!(result[0].distances[1].distance, 1);
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        let graph = test_fixture::build_graph(&IDS, &RELATIONSHIPS);
+        assert!(
+            ElementGraphAncestor::common_ancestors(&graph, &["pump".to_string()], |_| true)
+                .is_err()
+        );
+        let ids = vec!["pump".to_string(), "unknown".to_string()];
+        assert!(ElementGraphAncestor::common_ancestors(&graph, &ids, |_| true).is_err());
+    }
+}
@@ -0,0 +1,325 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+
+use crate::model::{element_graph::ElementGraph, model_error::ModelError, utils::Utils};
+
+static PAGERANK_DAMPING: f64 = 0.85;
+static PAGERANK_MAX_ITERATION: usize = 100;
+static PAGERANK_TOLERANCE: f64 = 1e-9;
+// Above this, betweenness is estimated from evenly spread source elements
+pub static BETWEENNESS_MAX_SOURCE: usize = 256;
+
+// Centrality metrics computed over the whole ElementGraph.
+// Betweenness is O(V.E), only computed when asked for.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CentralityMetric {
+    Degree,
+    InDegree,
+    OutDegree,
+    Betweenness,
+    PageRank,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CentralityScore {
+    pub element_id: String,
+    pub in_degree: usize,
+    pub out_degree: usize,
+    // None when the report was computed without betweenness
+    pub betweenness: Option<f64>,
+    pub pagerank: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CentralityReport {
+    pub scores: Vec<CentralityScore>,
+    // Sources the betweenness was computed from, all elements when exact
+    pub betweenness_source_count: Option<usize>,
+}
+
+pub struct ElementGraphCentrality;
+
+impl CentralityMetric {
+    pub fn parse(metric: &str) -> Result<Self, ModelError> {
+        match metric.to_lowercase().as_str() {
+            "degree" => Ok(CentralityMetric::Degree),
+            "in_degree" => Ok(CentralityMetric::InDegree),
+            "out_degree" => Ok(CentralityMetric::OutDegree),
+            "betweenness" => Ok(CentralityMetric::Betweenness),
+            "pagerank" => Ok(CentralityMetric::PageRank),
+            _ => Err(ModelError::InvalidInput(format!(
+                "Unknown centrality metric {}. Use degree, in_degree, out_degree, betweenness or pagerank",
+                metric
+            ))),
+        }
+    }
+}
+
+impl CentralityScore {
+    pub fn get_score(&self, metric: CentralityMetric) -> f64 {
+        match metric {
+            CentralityMetric::Degree => (self.in_degree + self.out_degree) as f64,
+            CentralityMetric::InDegree => self.in_degree as f64,
+            CentralityMetric::OutDegree => self.out_degree as f64,
+            CentralityMetric::Betweenness => self.betweenness.unwrap_or_default(),
+            CentralityMetric::PageRank => self.pagerank,
+        }
+    }
+}
+
+impl CentralityReport {
+    pub fn has_metric(&self, metric: CentralityMetric) -> bool {
+        metric != CentralityMetric::Betweenness || self.betweenness_source_count.is_some()
+    }
+
+    // Ranked scores, highest first, keeping the ones accepted by the filter
+    pub fn top<F>(&self, metric: CentralityMetric, top_n: usize, filter: F) -> Vec<&CentralityScore>
+    where
+        F: Fn(&CentralityScore) -> bool,
+    {
+        let mut ranked: Vec<&CentralityScore> = self.scores.iter().filter(|s| filter(s)).collect();
+        ranked.sort_by(|a, b| {
+            b.get_score(metric)
+                .total_cmp(&a.get_score(metric))
+                .then_with(|| a.element_id.cmp(&b.element_id))
+        });
+        ranked.truncate(top_n);
+        ranked
+    }
+}
+
+impl ElementGraphCentrality {
+    pub fn compute(graph: &ElementGraph, metric: CentralityMetric) -> CentralityReport {
+        let start_time = Instant::now();
+
+        // Index the graph so the algorithms can work on plain vectors
+        let mut ids = graph.get_all_elements();
+        ids.sort();
+        let index: HashMap<&str, usize> = ids
+            .iter()
+            .enumerate()
+            .map(|(i, id)| (id.as_str(), i))
+            .collect();
+        let out_adjacency: Vec<Vec<usize>> = ids
+            .iter()
+            .map(|id| {
+                graph
+                    .get_connection(id)
+                    .map(|c| {
+                        c.get_out_id()
+                            .iter()
+                            .filter_map(|p| index.get(p.get_element_id()).copied())
+                            .collect()
+                    })
+                    .unwrap_or_default()
+            })
+            .collect();
+
+        let mut in_degree = vec![0; ids.len()];
+        for targets in &out_adjacency {
+            for target in targets {
+                in_degree[*target] += 1;
+            }
+        }
+
+        let betweenness = match metric {
+            CentralityMetric::Betweenness => {
+                Some(Self::betweenness(&out_adjacency, BETWEENNESS_MAX_SOURCE))
+            }
+            _ => None,
+        };
+        let pagerank = Self::pagerank(&out_adjacency);
+
+        let scores = ids
+            .iter()
+            .enumerate()
+            .map(|(i, id)| CentralityScore {
+                element_id: id.clone(),
+                in_degree: in_degree[i],
+                out_degree: out_adjacency[i].len(),
+                betweenness: betweenness.as_ref().map(|(b, _)| b[i]),
+                pagerank: pagerank[i],
+            })
+            .collect();
+
+        Utils::log_time(start_time, "ElementGraphCentrality - compute");
+        CentralityReport {
+            scores,
+            betweenness_source_count: betweenness.map(|(_, source_count)| source_count),
+        }
+    }
+
+    // Brandes algorithm on unweighted directed graph. Above max_source elements,
+    // only max_source evenly spread sources are used and the sum is scaled up.
+    fn betweenness(adjacency: &[Vec<usize>], max_source: usize) -> (Vec<f64>, usize) {
+        let n = adjacency.len();
+        let mut centrality = vec![0.0; n];
+        let source_count = n.min(max_source);
+        if source_count == 0 {
+            return (centrality, 0);
+        }
+
+        let mut stack: Vec<usize> = Vec::with_capacity(n);
+        let mut predecessors: Vec<Vec<usize>> = vec![Vec::new(); n];
+        let mut sigma = vec![0.0_f64; n];
+        let mut distance = vec![-1_i64; n];
+        let mut delta = vec![0.0_f64; n];
+        let mut queue: VecDeque<usize> = VecDeque::new();
+
+        for source in (0..source_count).map(|i| i * n / source_count) {
+            stack.clear();
+            predecessors.iter_mut().for_each(|p| p.clear());
+            sigma.iter_mut().for_each(|s| *s = 0.0);
+            distance.iter_mut().for_each(|d| *d = -1);
+            delta.iter_mut().for_each(|d| *d = 0.0);
+
+            sigma[source] = 1.0;
+            distance[source] = 0;
+            queue.push_back(source);
+
+            while let Some(v) = queue.pop_front() {
+                stack.push(v);
+                for &w in &adjacency[v] {
+                    if distance[w] < 0 {
+                        distance[w] = distance[v] + 1;
+                        queue.push_back(w);
+                    }
+                    if distance[w] == distance[v] + 1 {
+                        sigma[w] += sigma[v];
+                        predecessors[w].push(v);
+                    }
+                }
+            }
+
+            while let Some(w) = stack.pop() {
+                for &v in &predecessors[w] {
+                    delta[v] += sigma[v] / sigma[w] * (1.0 + delta[w]);
+                }
+                if w != source {
+                    centrality[w] += delta[w];
+                }
+            }
+        }
+
+        let scale = n as f64 / source_count as f64;
+        centrality.iter_mut().for_each(|c| *c *= scale);
+        (centrality, source_count)
+    }
+
+    // Power iteration, dangling nodes spread their rank uniformly
+    fn pagerank(adjacency: &[Vec<usize>]) -> Vec<f64> {
+        let n = adjacency.len();
+        if n == 0 {
+            return Vec::new();
+        }
+
+        let base = (1.0 - PAGERANK_DAMPING) / n as f64;
+        let mut rank = vec![1.0 / n as f64; n];
+        let mut next_rank = vec![0.0; n];
+
+        for _ in 0..PAGERANK_MAX_ITERATION {
+            let dangling_rank: f64 = adjacency
+                .iter()
+                .enumerate()
+                .filter(|(_, targets)| targets.is_empty())
+                .map(|(i, _)| rank[i])
+                .sum();
+
+            next_rank
+                .iter_mut()
+                .for_each(|r| *r = base + PAGERANK_DAMPING * dangling_rank / n as f64);
+            for (v, targets) in adjacency.iter().enumerate() {
+                let share = PAGERANK_DAMPING * rank[v] / targets.len().max(1) as f64;
+                for &w in targets {
+                    next_rank[w] += share;
+                }
+            }
+
+            let diff: f64 = rank
+                .iter()
+                .zip(next_rank.iter())
+                .map(|(a, b)| (a - b).abs())
+                .sum();
+            std::mem::swap(&mut rank, &mut next_rank);
+            if diff < PAGERANK_TOLERANCE {
+                break;
+            }
+        }
+
+        rank
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::test_fixture;
+
+    // c1 -> c3 <- c2, c3 -> c4, c3 -> c5
+    const IDS: [&str; 5] = ["c1", "c2", "c3", "c4", "c5"];
+    const RELATIONSHIPS: [(&str, &str, &str); 4] = [
+        ("r1", "c1", "c3"),
+        ("r2", "c2", "c3"),
+        ("r3", "c3", "c4"),
+        ("r4", "c3", "c5"),
+    ];
+
+    #[test]
+    fn test_degree() {
+        let report = ElementGraphCentrality::compute(
+            &test_fixture::build_graph(&IDS, &RELATIONSHIPS),
+            CentralityMetric::Degree,
+        );
+        let top = report.top(CentralityMetric::Degree, 1, |_| true);
+        assert_eq!(top[0].element_id, "c3");
+        assert_eq!(top[0].in_degree, 2);
+        assert_eq!(top[0].out_degree, 2);
+        // Betweenness left out
+        assert_eq!(top[0].betweenness, None);
+        assert!(!report.has_metric(CentralityMetric::Betweenness));
+    }
+
+    #[test]
+    fn test_betweenness() {
+        let report = ElementGraphCentrality::compute(
+            &test_fixture::build_graph(&IDS, &RELATIONSHIPS),
+            CentralityMetric::Betweenness,
+        );
+        assert_eq!(report.betweenness_source_count, Some(5));
+        let top = report.top(CentralityMetric::Betweenness, 5, |_| true);
+        // Every path c1/c2 -> c4/c5 goes through c3
+        assert_eq!(top[0].element_id, "c3");
+        assert_eq!(top[0].betweenness, Some(4.0));
+        assert_eq!(top[1].betweenness, Some(0.0));
+    }
+
+    #[test]
+    fn test_betweenness_sampled() {
+        // c1 -> c2 -> c3 -> c4, sources c1 and c3 scaled by 2
+        let adjacency = vec![vec![1], vec![2], vec![3], vec![]];
+        let (exact, source_count) = ElementGraphCentrality::betweenness(&adjacency, 10);
+        assert_eq!(source_count, 4);
+        assert_eq!(exact, vec![0.0, 2.0, 2.0, 0.0]);
+
+        let (sampled, source_count) = ElementGraphCentrality::betweenness(&adjacency, 2);
+        assert_eq!(source_count, 2);
+        assert_eq!(sampled, vec![0.0, 4.0, 2.0, 0.0]);
+    }
+
+    #[test]
+    fn test_pagerank() {
+        let report = ElementGraphCentrality::compute(
+            &test_fixture::build_graph(&IDS, &RELATIONSHIPS),
+            CentralityMetric::PageRank,
+        );
+        let total: f64 = report.scores.iter().map(|s| s.pagerank).sum();
+        assert!((total - 1.0).abs() < 1e-6);
+
+        let top = report.top(CentralityMetric::PageRank, 3, |s| s.element_id != "c3");
+        assert!(top.iter().all(|s| s.element_id != "c3"));
+        assert!(top[0].element_id == "c4" || top[0].element_id == "c5");
+    }
+}
@@ -0,0 +1,148 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+
+use crate::model::{
+    element_graph::{ElementGraph, Path},
+    model_error::ModelError,
+    utils::Utils,
+};
+
+// Downstream impact of an element following the out_ids side of the ElementGraph
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImpactStep {
+    pub relationship_id: String,
+    pub source_id: String,
+    pub target_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImpactedElement {
+    pub element_id: String,
+    pub depth: usize,
+    // Shortest relationship chain from the changed element
+    pub path: Vec<ImpactStep>,
+}
+
+pub struct ElementGraphImpact;
+
+impl ElementGraphImpact {
+    pub fn downstream<F>(
+        graph: &ElementGraph,
+        element_id: &str,
+        is_relationship_selected: F,
+        max_depth: usize,
+    ) -> Result<Vec<ImpactedElement>, ModelError>
+    where
+        F: Fn(&Path) -> bool,
+    {
+        let start_time = Instant::now();
+        if graph.get_connection(element_id).is_none() {
+            return Err(ModelError::ParsingError(format!("Element: {}", element_id)));
+        }
+
+        // BFS keeping the relationship used to reach each element
+        let mut reached_by: HashMap<&str, (&str, &str, usize)> = HashMap::new();
+        let mut order: Vec<&str> = Vec::new();
+        let mut queue: VecDeque<(&str, usize)> = VecDeque::new();
+        queue.push_back((element_id, 0));
+
+        while let Some((id, depth)) = queue.pop_front() {
+            if depth >= max_depth {
+                continue;
+            }
+            let Some(connector) = graph.get_connection(id) else {
+                continue;
+            };
+            for path in connector.get_out_id() {
+                let target = path.get_element_id();
+                if !is_relationship_selected(&path)
+                    || target == element_id
+                    || reached_by.contains_key(target)
+                {
+                    continue;
+                }
+                reached_by.insert(target, (path.get_relationship_id(), id, depth + 1));
+                order.push(target);
+                queue.push_back((target, depth + 1));
+            }
+        }
+
+        let result = order
+            .into_iter()
+            .map(|id| {
+                let depth = reached_by[id].2;
+                let mut path: Vec<ImpactStep> = Vec::with_capacity(depth);
+                let mut current = id;
+                while let Some((relationship_id, source_id, _)) = reached_by.get(current) {
+                    path.push(ImpactStep {
+                        relationship_id: relationship_id.to_string(),
+                        source_id: source_id.to_string(),
+                        target_id: current.to_string(),
+                    });
+                    current = source_id;
+                }
+                path.reverse();
+
+                ImpactedElement {
+                    element_id: id.to_owned(),
+                    depth,
+                    path,
+                }
+            })
+            .collect();
+
+        Utils::log_time(start_time, "ElementGraphImpact - downstream");
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::test_fixture;
+
+    const IDS: [&str; 5] = ["c1", "c2", "c3", "c4", "c5"];
+    const RELATIONSHIPS: [(&str, &str, &str); 5] = [
+        ("r1", "c1", "c2"),
+        ("r2", "c2", "c3"),
+        ("r3", "c1", "c3"),
+        ("r4", "c3", "c4"),
+        ("r5", "c4", "c1"),
+    ];
+
+    #[test]
+    fn test_downstream() {
+        let graph = test_fixture::build_graph(&IDS, &RELATIONSHIPS);
+        let result = ElementGraphImpact::downstream(&graph, "c1", |_| true, 10).unwrap();
+
+        let ids: Vec<&str> = result.iter().map(|e| e.element_id.as_str()).collect();
+        assert_eq!(ids, vec!["c2", "c3", "c4"]);
+
+        // Shortest chain to c4 goes through r3
+        let c4 = &result[2];
+        assert_eq!(c4.depth, 2);
+        let relationships: Vec<&str> = c4.path.iter().map(|s| s.relationship_id.as_str()).collect();
+        assert_eq!(relationships, vec!["r3", "r4"]);
+        assert_eq!(c4.path[0].source_id, "c1");
+        assert_eq!(c4.path[1].target_id, "c4");
+    }
+
+    #[test]
+    fn test_downstream_with_limit_and_filter() {
+        let graph = test_fixture::build_graph(&IDS, &RELATIONSHIPS);
+        let result = ElementGraphImpact::downstream(&graph, "c1", |_| true, 1).unwrap();
+        assert_eq!(result.len(), 2);
+
+        let result =
+            ElementGraphImpact::downstream(&graph, "c1", |p| p.get_relationship_id() != "r3", 10)
+                .unwrap();
+        let c3 = result.iter().find(|e| e.element_id == "c3").unwrap();
+        assert_eq!(c3.depth, 2);
+        assert_eq!(c3.path[0].relationship_id, "r1");
+
+        assert!(ElementGraphImpact::downstream(&graph, "unknown", |_| true, 10).is_err());
+    }
+}
@@ -0,0 +1,224 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+
+use crate::model::{
+    element_graph::{ElementGraph, Path},
+    utils::Utils,
+};
+
+// Topological order and layering of the ElementGraph restricted to selected relationships
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TopologicalOrder {
+    // Element ids grouped by layer. Layer n only depends on layers < n
+    Layers(Vec<Vec<String>>),
+    // Cycle preventing the ordering as (element id, relationship id to next element)
+    Cycle(Vec<CycleStep>),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CycleStep {
+    pub element_id: String,
+    pub relationship_id: String,
+}
+
+pub struct ElementGraphLayering;
+
+impl ElementGraphLayering {
+    pub fn compute<F>(
+        graph: &ElementGraph,
+        is_relationship_selected: F,
+        include_isolated: bool,
+    ) -> TopologicalOrder
+    where
+        F: Fn(&Path) -> bool,
+    {
+        let start_time = Instant::now();
+
+        // Collect selected edges
+        let mut ids = graph.get_all_elements();
+        ids.sort();
+        let mut out_edges: HashMap<&str, Vec<(&str, &str)>> = HashMap::new();
+        let mut in_edges: HashMap<&str, Vec<(&str, &str)>> = HashMap::new();
+        let mut in_degree: HashMap<&str, usize> = HashMap::new();
+        let mut connected: HashSet<&str> = HashSet::new();
+        for id in &ids {
+            in_degree.entry(id.as_str()).or_insert(0);
+            if let Some(connector) = graph.get_connection(id) {
+                for path in connector.get_out_id() {
+                    if !is_relationship_selected(&path)
+                        || graph.get_connection(path.get_element_id()).is_none()
+                    {
+                        continue;
+                    }
+                    out_edges
+                        .entry(id.as_str())
+                        .or_default()
+                        .push((path.get_relationship_id(), path.get_element_id()));
+                    in_edges
+                        .entry(path.get_element_id())
+                        .or_default()
+                        .push((path.get_relationship_id(), id.as_str()));
+                    *in_degree.entry(path.get_element_id()).or_insert(0) += 1;
+                    connected.insert(id.as_str());
+                    connected.insert(path.get_element_id());
+                }
+            }
+        }
+
+        let nodes: Vec<&str> = ids
+            .iter()
+            .map(|id| id.as_str())
+            .filter(|id| include_isolated || connected.contains(id))
+            .collect();
+
+        // Kahn algorithm, layer is the longest distance from a source
+        let mut layer: HashMap<&str, usize> = HashMap::new();
+        let mut queue: VecDeque<&str> = nodes
+            .iter()
+            .copied()
+            .filter(|id| in_degree[id] == 0)
+            .collect();
+        queue.iter().for_each(|id| {
+            layer.insert(id, 0);
+        });
+
+        let mut visited_count = 0;
+        while let Some(id) = queue.pop_front() {
+            visited_count += 1;
+            let current_layer = layer[id];
+            for (_, target) in out_edges.get(id).map(|e| e.as_slice()).unwrap_or(&[]) {
+                let target_layer = layer.entry(target).or_insert(0);
+                *target_layer = (*target_layer).max(current_layer + 1);
+
+                let degree = in_degree.get_mut(target).unwrap();
+                *degree -= 1;
+                if *degree == 0 {
+                    queue.push_back(target);
+                }
+            }
+        }
+
+        let result = if visited_count < nodes.len() {
+            let remaining: HashSet<&str> = nodes
+                .iter()
+                .copied()
+                .filter(|id| in_degree[id] > 0)
+                .collect();
+            TopologicalOrder::Cycle(Self::find_cycle(&remaining, &in_edges))
+        } else {
+            let layer_count = layer.values().max().map_or(0, |l| l + 1);
+            let mut layers: Vec<Vec<String>> = vec![Vec::new(); layer_count];
+            for id in nodes {
+                layers[layer[id]].push(id.to_owned());
+            }
+            TopologicalOrder::Layers(layers)
+        };
+
+        Utils::log_time(start_time, "ElementGraphLayering - compute");
+        result
+    }
+
+    // Walk backward from any remaining node. Every remaining node still has an unprocessed
+    // parent, so the walk must end up in a cycle
+    fn find_cycle(
+        remaining: &HashSet<&str>,
+        in_edges: &HashMap<&str, Vec<(&str, &str)>>,
+    ) -> Vec<CycleStep> {
+        let mut current = match remaining.iter().min() {
+            Some(start) => *start,
+            None => return Vec::new(),
+        };
+
+        // (parent, relationship from parent to current)
+        let mut steps: Vec<(&str, &str)> = Vec::new();
+        let mut position: HashMap<&str, usize> = HashMap::new();
+        loop {
+            if let Some(&index) = position.get(current) {
+                return steps[index..]
+                    .iter()
+                    .rev()
+                    .map(|(element_id, relationship_id)| CycleStep {
+                        element_id: element_id.to_string(),
+                        relationship_id: relationship_id.to_string(),
+                    })
+                    .collect();
+            }
+            position.insert(current, steps.len());
+
+            let parent = in_edges
+                .get(current)
+                .and_then(|edges| edges.iter().find(|(_, s)| remaining.contains(s)));
+            match parent {
+                Some((relationship_id, source)) => {
+                    steps.push((source, relationship_id));
+                    current = source;
+                }
+                None => return Vec::new(),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::test_fixture;
+
+    const IDS: [&str; 6] = ["c1", "c2", "c3", "c4", "c5", "c6"];
+    const RELATIONSHIPS: [(&str, &str, &str); 5] = [
+        ("r1", "c1", "c2"),
+        ("r2", "c2", "c3"),
+        ("r3", "c1", "c3"),
+        ("r4", "c3", "c4"),
+        ("r5", "c4", "c2"),
+    ];
+
+    #[test]
+    fn test_layers() {
+        let graph = test_fixture::build_graph(&IDS, &RELATIONSHIPS);
+        let result =
+            ElementGraphLayering::compute(&graph, |p| p.get_relationship_id() != "r5", false);
+        match result {
+            TopologicalOrder::Layers(layers) => {
+                assert_eq!(layers.len(), 4);
+                assert_eq!(layers[0], vec!["c1"]);
+                assert_eq!(layers[1], vec!["c2"]);
+                assert_eq!(layers[2], vec!["c3"]);
+                assert_eq!(layers[3], vec!["c4"]);
+            }
+            TopologicalOrder::Cycle(_) => panic!("Unexpected cycle"),
+        }
+    }
+
+    #[test]
+    fn test_layers_include_isolated() {
+        let graph = test_fixture::build_graph(&IDS, &RELATIONSHIPS);
+        let result =
+            ElementGraphLayering::compute(&graph, |p| p.get_relationship_id() == "r1", true);
+        match result {
+            TopologicalOrder::Layers(layers) => {
+                assert_eq!(layers[0], vec!["c1", "c3", "c4", "c5", "c6"]);
+                assert_eq!(layers[1], vec!["c2"]);
+            }
+            TopologicalOrder::Cycle(_) => panic!("Unexpected cycle"),
+        }
+    }
+
+    #[test]
+    fn test_cycle() {
+        let graph = test_fixture::build_graph(&IDS, &RELATIONSHIPS);
+        let result = ElementGraphLayering::compute(&graph, |_| true, false);
+        match result {
+            TopologicalOrder::Cycle(cycle) => {
+                let relationships: Vec<&str> =
+                    cycle.iter().map(|s| s.relationship_id.as_str()).collect();
+                assert_eq!(relationships, vec!["r2", "r4", "r5"]);
+            }
+            TopologicalOrder::Layers(_) => panic!("Cycle not detected"),
+        }
+    }
+}
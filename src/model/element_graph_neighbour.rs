@@ -0,0 +1,133 @@
+use std::collections::{HashSet, VecDeque};
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+
+use crate::model::{
+    config::TraversalDirection, element_graph::ElementGraph, model_error::ModelError, utils::Utils,
+};
+
+// Neighbours of an element within a number of hops
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Neighbour {
+    pub element_id: String,
+    pub hop: usize,
+    // Direction of the connecting relationship seen from via_element_id
+    pub direction: TraversalDirection,
+    pub relationship_id: String,
+    pub via_element_id: String,
+}
+
+pub struct ElementGraphNeighbour;
+
+impl ElementGraphNeighbour {
+    pub fn neighbours(
+        graph: &ElementGraph,
+        element_id: &str,
+        direction: TraversalDirection,
+        max_hop: usize,
+    ) -> Result<Vec<Neighbour>, ModelError> {
+        let start_time = Instant::now();
+        if graph.get_connection(element_id).is_none() {
+            return Err(ModelError::ParsingError(format!("Element: {}", element_id)));
+        }
+
+        // BFS, each neighbour is reported once at its closest hop
+        let mut result: Vec<Neighbour> = Vec::new();
+        let mut visited: HashSet<&str> = HashSet::from([element_id]);
+        let mut queue: VecDeque<(&str, usize)> = VecDeque::from([(element_id, 0)]);
+
+        while let Some((id, hop)) = queue.pop_front() {
+            if hop >= max_hop {
+                continue;
+            }
+            let Some(connector) = graph.get_connection(id) else {
+                continue;
+            };
+
+            let out_paths = connector
+                .get_out_id()
+                .into_iter()
+                .map(|p| (p, TraversalDirection::Out));
+            let in_paths = connector
+                .get_in_id()
+                .into_iter()
+                .map(|p| (p, TraversalDirection::In));
+            let paths: Vec<_> = match direction {
+                TraversalDirection::Out => out_paths.collect(),
+                TraversalDirection::In => in_paths.collect(),
+                TraversalDirection::Both => out_paths.chain(in_paths).collect(),
+            };
+
+            for (path, path_direction) in paths {
+                let next_id = path.get_element_id();
+                if !visited.insert(next_id) {
+                    continue;
+                }
+                result.push(Neighbour {
+                    element_id: next_id.to_owned(),
+                    hop: hop + 1,
+                    direction: path_direction,
+                    relationship_id: path.get_relationship_id().to_owned(),
+                    via_element_id: id.to_owned(),
+                });
+                queue.push_back((next_id, hop + 1));
+            }
+        }
+
+        Utils::log_time(start_time, "ElementGraphNeighbour - neighbours");
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::test_fixture;
+
+    const IDS: [&str; 5] = ["c1", "c2", "c3", "c4", "c5"];
+    const RELATIONSHIPS: [(&str, &str, &str); 4] = [
+        ("r1", "c1", "c2"),
+        ("r2", "c2", "c3"),
+        ("r3", "c3", "c4"),
+        ("r4", "c5", "c2"),
+    ];
+
+    #[test]
+    fn test_neighbours_out() {
+        let graph = test_fixture::build_graph(&IDS, &RELATIONSHIPS);
+        let result =
+            ElementGraphNeighbour::neighbours(&graph, "c2", TraversalDirection::Out, 1).unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].element_id, "c3");
+        assert_eq!(result[0].relationship_id, "r2");
+        assert_eq!(result[0].direction, TraversalDirection::Out);
+
+        let result =
+            ElementGraphNeighbour::neighbours(&graph, "c2", TraversalDirection::Out, 2).unwrap();
+        let ids: Vec<&str> = result.iter().map(|n| n.element_id.as_str()).collect();
+        assert_eq!(ids, vec!["c3", "c4"]);
+        assert_eq!(result[1].hop, 2);
+        assert_eq!(result[1].via_element_id, "c3");
+    }
+
+    #[test]
+    fn test_neighbours_in_and_both() {
+        let graph = test_fixture::build_graph(&IDS, &RELATIONSHIPS);
+        let result =
+            ElementGraphNeighbour::neighbours(&graph, "c2", TraversalDirection::In, 5).unwrap();
+        let ids: Vec<&str> = result.iter().map(|n| n.element_id.as_str()).collect();
+        assert_eq!(ids, vec!["c1", "c5"]);
+        assert!(result.iter().all(|n| n.direction == TraversalDirection::In));
+
+        let result =
+            ElementGraphNeighbour::neighbours(&graph, "c2", TraversalDirection::Both, 1).unwrap();
+        assert_eq!(result.len(), 3);
+
+        assert!(
+            ElementGraphNeighbour::neighbours(&graph, "unknown", TraversalDirection::Both, 1)
+                .is_err()
+        );
+    }
+}
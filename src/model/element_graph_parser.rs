@@ -1,6 +1,7 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use crate::info;
 use crate::model::{
-        config::{OutputGraph, OutputLine, OutputToken, RelationshipDirection}, cubs_model::ModelData, element_graph::{ElementConnector, ElementGraph, Path}, model_error::ModelError
+        config::{OutputGraph, OutputLine, OutputToken, RelationshipDirection}, cubs_model::CusObject, element_graph::{ElementConnector, ElementGraph, Path}, model_error::ModelError, model_snapshot::ModelSnapshot
     };
 
 pub struct ElementGraphParser;
@@ -15,7 +16,7 @@ impl ElementGraphParser {
         let mut partial_graph = ElementGraph::new();
 
         // Find target element connector
-        println!("[ElementGraphParser - parse_graph ] retrieving {} from {} connection", target_element_id, full_graph.get_connection_count());
+        info!("[ElementGraphParser - parse_graph ] retrieving {} from {} connection", target_element_id, full_graph.get_connection_count());
         let target_connector =
             full_graph
                 .get_connection(target_element_id)
@@ -24,6 +25,8 @@ impl ElementGraphParser {
                     target_element_id
                 )))?;
 
+        partial_graph.add_connector(target_element_id);
+
         // Iterate up from target to parent
         Self::parse_parent(
             full_graph,
@@ -42,23 +45,13 @@ impl ElementGraphParser {
             children_level_limit,
         );
 
-        // Add target
-        let mut target_connector_cloned = target_connector.clone();
-        if ancestor_level_limit == 0 {
-            target_connector_cloned.clear_in_id();
-        }
-        if children_level_limit == 0 {
-            target_connector_cloned.clear_out_id();
-        }
-        partial_graph.push_connector(&target_connector.get_element_id(), target_connector_cloned);
-
         Ok(partial_graph)
     }
 
     fn parse_parent(
         source_graph: &ElementGraph,
         target_graph: &mut ElementGraph,
-        current_element_connnector: &ElementConnector,
+        current_element_connnector: ElementConnector,
         current_level: u32,
         limit: u32,
     ) {
@@ -67,32 +60,24 @@ impl ElementGraphParser {
             return;
         }
 
-        let current_element_id = current_element_connnector.get_element_id();
-        let ids: Vec<&Path> = current_element_connnector.get_in_id();
+        let ids: Vec<Path> = current_element_connnector.get_in_id();
         if ids.is_empty() {
             return;
         }
 
         // Executing  For every id in the path add to the target graph
         for path in ids {
-            let id = &path.1;
-            let relationship_id = &path.0;
-            if let Some(parent_connector) = source_graph.get_connection(&id) {
-                println!("[ElementGraphParser - parse_parent] Adding element {} with relationship {} to graph at level: {}", parent_connector.get_element_id(), relationship_id, current_level);
-
-                let mut cloned_parent_connector = parent_connector.clone();
-                // Truncate the path if reach limit
-                if current_level == limit {
-                    cloned_parent_connector.clear_in_id();
+            let id = path.get_element_id();
+            let relationship_id = path.get_relationship_id();
+            if let Some(parent_connector) = source_graph.get_connection(id) {
+                info!("[ElementGraphParser - parse_parent] Adding element {} with relationship {} to graph at level: {}", parent_connector.get_element_id(), relationship_id, current_level);
+
+                // Add to target graph, only the traversed relationship is kept
+                target_graph.add_connector(id);
+                if target_graph.get_relationship(relationship_id).is_none() {
+                    target_graph.connect_path(&path);
                 }
 
-                // prune out other branch
-                cloned_parent_connector.retain_out_id(current_element_id);
-
-                // Add to target graph
-                target_graph.push_connector(&id.to_owned(), cloned_parent_connector);
-                target_graph.add_connected_relationship(&relationship_id.to_owned());
-
                 // Recrusive call
                 ElementGraphParser::parse_parent(
                     source_graph,
@@ -103,7 +88,7 @@ impl ElementGraphParser {
                     // &path_retrieval,
                 );
             } else {
-                println!("[ElementGraphParser - parse_parent] Error parsing {}", id);
+                info!("[ElementGraphParser - parse_parent] Error parsing {}", id);
                 break;
             }
         }
@@ -112,7 +97,7 @@ impl ElementGraphParser {
     fn parse_child(
         source_graph: &ElementGraph,
         target_graph: &mut ElementGraph,
-        current_element_connnector: &ElementConnector,
+        current_element_connnector: ElementConnector,
         current_level: u32,
         limit: u32,
     ) {
@@ -121,28 +106,24 @@ impl ElementGraphParser {
             return;
         }
 
-        let ids: Vec<&Path> = current_element_connnector.get_out_id();
+        let ids: Vec<Path> = current_element_connnector.get_out_id();
         if ids.is_empty() {
             return;
         }
 
         // Executing  For every id in the path add to the target graph
         for path in ids {
-            let id = &path.1;
-            let relationship_id = &path.0;
-            if let Some(parent_connector) = source_graph.get_connection(&id) {
+            let id = path.get_element_id();
+            let relationship_id = path.get_relationship_id();
+            if let Some(parent_connector) = source_graph.get_connection(id) {
                 // println!("[ElementGraphParser - parse_child] Adding element {} with relationship {} to graph at level: {}", parent_connector.get_element_id(), relationship_id, current_level);
 
-                let mut cloned_parent_connector = parent_connector.clone();
-                // Truncate the path if reach limit
-                if current_level == limit {
-                    cloned_parent_connector.clear_out_id();
+                // Add to target graph, only the traversed relationship is kept
+                target_graph.add_connector(id);
+                if target_graph.get_relationship(relationship_id).is_none() {
+                    target_graph.connect_path(&path);
                 }
 
-                // Add to target graph
-                target_graph.push_connector(&id.to_owned(), cloned_parent_connector);
-                target_graph.add_connected_relationship(&relationship_id.to_owned());
-
                 // Recrusive call
                 ElementGraphParser::parse_child(
                     source_graph,
@@ -152,7 +133,7 @@ impl ElementGraphParser {
                     limit,
                 );
             } else {
-                println!("[ElementGraphParser - parse_child] Error parsing {}", id);
+                info!("[ElementGraphParser - parse_child] Error parsing {}", id);
                 break;
             }
         }
@@ -161,13 +142,24 @@ impl ElementGraphParser {
     pub fn build_output(
         full_graph: &ElementGraph,
         target_element_id: &str,
-        model_data: &ModelData,
+        snapshot: &ModelSnapshot,
+    ) -> Result<OutputGraph<String>, ModelError> {
+        Self::build_output_with_limit(full_graph, target_element_id, snapshot, 2, 5)
+    }
+
+    pub fn build_output_with_limit(
+        full_graph: &ElementGraph,
+        target_element_id: &str,
+        snapshot: &ModelSnapshot,
+        ancestor_level_limit: u32,
+        children_level_limit: u32,
     ) -> Result<OutputGraph<String>, ModelError> {
         // Peform DFS
         let mut output = OutputGraph {
             parent_lines: Vec::new(),
             child_lines: Vec::new(),
             elements_data: HashMap::new(),
+            relationships_data: HashMap::new(),
         };
         let current_element = full_graph.get_connection(target_element_id);
 
@@ -178,9 +170,11 @@ impl ElementGraphParser {
         Self::dfs(
             full_graph,
             current_element,
+            None,
             &mut traversed_element_ids,
+            &mut HashSet::new(),
             0,
-            5,
+            children_level_limit,
             &RelationshipDirection::Child,
             &mut output,
         );
@@ -189,9 +183,11 @@ impl ElementGraphParser {
         Self::dfs(
             full_graph,
             current_element,
+            None,
             &mut traversed_element_ids,
+            &mut HashSet::new(),
             0,
-            2,
+            ancestor_level_limit,
             &RelationshipDirection::Parent,
             &mut output,
         );
@@ -201,21 +197,46 @@ impl ElementGraphParser {
         let element_map = traversed_element_ids
             .iter()
             .filter_map(|id| {
-                model_data
-                    .get_element_with_id(id)
+                snapshot
+                    .get_element(id)
                     .and_then(|element| serde_json::to_value(element.get_common_fields_values_map()).ok())
                     .map(|value| (id.clone(), value))
             })
             .collect();
         output.elements_data = element_map;
 
+        // Retrieve relationships
+        let traversed_relationship_ids: HashSet<&str> = output
+            .parent_lines
+            .iter()
+            .chain(output.child_lines.iter())
+            .flat_map(|l| l.line.iter())
+            .filter_map(|token| match token {
+                OutputToken::Relationship(id) => Some(id.as_str()),
+                _ => None,
+            })
+            .collect();
+        let relationship_map = traversed_relationship_ids
+            .into_iter()
+            .filter_map(|id| snapshot.get_relationship(id))
+            .filter_map(|r| {
+                serde_json::to_value(r.get_common_fields_values_map())
+                    .ok()
+                    .map(|value| (r.id.clone(), value))
+            })
+            .collect();
+        output.relationships_data = relationship_map;
+
         Ok(output)
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn dfs(
         full_graph: &ElementGraph,
-        current_element: Option<&ElementConnector>,
+        current_element: Option<ElementConnector>,
+        relationship_id: Option<&str>,
         traversed_element_ids: &mut Vec<String>,
+        expanded_element_ids: &mut HashSet<String>,
         level: u32,
         limit: u32,
         direcion: &RelationshipDirection,
@@ -227,8 +248,12 @@ impl ElementGraphParser {
                 return;
             }
 
-            // Marked as traversed
-            traversed_element_ids.push(current_element.get_element_id().to_owned());
+            // Marked as traversed, children are only expanded the first time
+            // so cycles and diamonds don't multiply the paths
+            let is_expanded = !expanded_element_ids.insert(current_element.get_element_id().to_owned());
+            if !is_expanded {
+                traversed_element_ids.push(current_element.get_element_id().to_owned());
+            }
 
             // Generate token
             let mut output_line = OutputLine::new();
@@ -246,6 +271,11 @@ impl ElementGraphParser {
                 };
             }
 
+            // Relationship
+            if let Some(relationship_id) = relationship_id {
+                output_line.push(OutputToken::Relationship(relationship_id.to_owned()));
+            }
+
             //Value
             output_line.push(OutputToken::Value(
                 current_element.get_element_id().to_owned(),
@@ -256,6 +286,9 @@ impl ElementGraphParser {
                 RelationshipDirection::Parent => result.parent_lines.push(output_line),
                 RelationshipDirection::Child => result.child_lines.push(output_line),
             };
+            if is_expanded {
+                return;
+            }
 
             //Child
             let childs = match direcion {
@@ -264,13 +297,15 @@ impl ElementGraphParser {
             };
 
             for child in childs {
-                let element_id = &child.1;
-                let _rel_id = &child.0;
-                let next_element = full_graph.get_connection(&element_id);
+                let element_id = child.get_element_id();
+                let rel_id = child.get_relationship_id();
+                let next_element = full_graph.get_connection(element_id);
                 Self::dfs(
                     full_graph,
                     next_element,
+                    Some(rel_id),
                     traversed_element_ids,
+                    expanded_element_ids,
                     level + 1,
                     limit,
                     direcion,
@@ -284,11 +319,13 @@ impl ElementGraphParser {
 }
 
 #[cfg(test)]
+#[allow(clippy::assertions_on_constants)]
 mod tests {
     use crate::model::{
         cubs_model::ModelData,
-        element_graph::{ElementConnector, ElementGraph},
+        element_graph::ElementGraph,
         element_graph_parser::ElementGraphParser,
+        model_snapshot::ModelSnapshot,
     };
 
     #[test]
@@ -489,11 +526,41 @@ mod tests {
         }
 
         let output =
-            ElementGraphParser::build_output(&parse_graph, target, &ModelData::default())
-                .unwrap();
+            ElementGraphParser::build_output(
+                &parse_graph,
+                target,
+                &ModelSnapshot::build(ModelData::default()),
+            )
+            .unwrap();
 
         println!("OUTPUT {:?}", output);
 
         assert!(true);
     }
+
+    #[test]
+    fn test_build_output_with_cycle() {
+        // c1 -> c2 -> c3 -> c1 and c1 -> c3
+        let mut graph = ElementGraph::new();
+        for id in ["c1", "c2", "c3"] {
+            graph.add_connector(id);
+        }
+        graph.connect("r1", "c1", "c2");
+        graph.connect("r2", "c2", "c3");
+        graph.connect("r3", "c3", "c1");
+        graph.connect("r4", "c1", "c3");
+
+        let output = ElementGraphParser::build_output_with_limit(
+            &graph,
+            "c1",
+            &ModelSnapshot::build(ModelData::default()),
+            20,
+            20,
+        )
+        .unwrap();
+
+        // Revisited elements are listed once more without their children
+        assert_eq!(output.child_lines.len(), 5);
+        assert_eq!(output.parent_lines.len(), 5);
+    }
 }
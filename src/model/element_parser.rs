@@ -1,5 +1,6 @@
 use std::time::Instant;
 
+use crate::info;
 use crate::model::{
     cubs_model::{Element, Relationship},
     element_graph::ElementGraph,
@@ -23,20 +24,20 @@ impl ElementConnectorBuilder {
         let mut graph = ElementGraph::new();
 
         // For each elements build a connector
-        println!("[ElementConnectorBuilder - build_graph: Building graph]");
+        info!("[ElementConnectorBuilder - build_graph: Building graph]");
         elements.iter().for_each(|e| {
             graph.add_connector(&e.id);
         });
-        println!(
+        info!(
             "[ElementConnectorBuilder - build_graph: Built {} connector]",
             graph.get_connection_count()
         );
 
         // For each relationship connect connector
         relationship.iter().for_each(|r| {
-            graph.connect(&r.id, &r.source_id, &r.target_id);
+            graph.connect_relationship(r);
         });
-        println!(
+        info!(
             "[ElementConnectorBuilder - build_graph: Built {} relationship]",
             graph.get_connected_relationship_count()
         );
@@ -64,9 +65,8 @@ impl ElementConnectorBuilder {
 
         //Log time
         let elapsed_time = start_time.elapsed();
-        println!(
-            "[Execution time] {} - {:?}",
-            "ElementConnectorBuilder - build_graph", elapsed_time
+        info!(
+            "[Execution time] ElementConnectorBuilder - build_graph - {:?}", elapsed_time
         );
 
         Ok(graph)
@@ -0,0 +1,417 @@
+use std::fmt;
+use std::io::Write;
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::info;
+use crate::model::{
+    cubs_model::{CusObject, FacetType, ModelData},
+    model_error::ModelError,
+    utils::Utils,
+};
+
+// Export of a full model into graph exchange formats (GraphML / JSON Graph Format)
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphExportFormat {
+    GraphMl,
+    Jgf,
+}
+
+// Facet value flattened into a node/edge attribute. Spec format: <facetType>:<json pointer>
+#[derive(Debug)]
+pub struct FacetAttribute {
+    pub facet_type: FacetType,
+    pub pointer: String,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GraphExportSummary {
+    pub node_count: usize,
+    pub edge_count: usize,
+}
+
+pub struct GraphExporter;
+
+impl GraphExportFormat {
+    pub fn parse(format: &str) -> Result<Self, ModelError> {
+        match format.to_lowercase().as_str() {
+            "graphml" => Ok(GraphExportFormat::GraphMl),
+            "jgf" | "json" => Ok(GraphExportFormat::Jgf),
+            _ => Err(ModelError::InvalidInput(format!(
+                "Unsupported export format {}. Use graphml or jgf",
+                format
+            ))),
+        }
+    }
+
+    pub fn file_extension(&self) -> &str {
+        match self {
+            GraphExportFormat::GraphMl => "graphml",
+            GraphExportFormat::Jgf => "json",
+        }
+    }
+}
+
+impl fmt::Display for GraphExportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GraphExportFormat::GraphMl => write!(f, "graphml"),
+            GraphExportFormat::Jgf => write!(f, "jgf"),
+        }
+    }
+}
+
+impl FacetAttribute {
+    pub fn parse(spec: &str) -> Result<Self, ModelError> {
+        let (facet, pointer) = spec
+            .split_once(':')
+            .ok_or(ModelError::InvalidInput(format!(
+                "Facet attribute {} must be in the format <facetType>:<pointer>",
+                spec
+            )))?;
+
+        let facet_type = FacetType::parse(facet).ok_or(ModelError::InvalidInput(format!(
+            "Unknown facet type {}. Use coreFacets, dynamicFacets or facets",
+            facet
+        )))?;
+
+        if !pointer.starts_with('/') {
+            return Err(ModelError::InvalidInput(format!(
+                "Facet pointer {} must start with /",
+                pointer
+            )));
+        }
+
+        Ok(FacetAttribute {
+            name: format!("{}{}", facet, pointer),
+            facet_type,
+            pointer: pointer.to_owned(),
+        })
+    }
+
+    pub fn parse_all(specs: &[String]) -> Result<Vec<Self>, ModelError> {
+        specs.iter().map(|s| Self::parse(s)).collect()
+    }
+
+    // Resolve pointer against the facet map of the object
+    pub fn get_value<T: CusObject>(&self, obj: &T) -> Option<Value> {
+        let facets = match self.facet_type {
+            FacetType::CoreFacets => obj.get_core_facet(),
+            FacetType::DynamicFacets => obj.get_dynamic_facet(),
+            FacetType::Facets => obj.get_facet(),
+        };
+
+        let mut segments = self.pointer.splitn(3, '/').skip(1);
+        let key = segments.next()?.replace("~1", "/").replace("~0", "~");
+        let value = facets.get(&key)?;
+        match segments.next() {
+            Some(rest) => value.pointer(&format!("/{}", rest)).cloned(),
+            None => Some(value.clone()),
+        }
+    }
+}
+
+impl GraphExporter {
+    pub fn export<W: Write>(
+        model: &ModelData,
+        format: GraphExportFormat,
+        attributes: &[FacetAttribute],
+        writer: W,
+    ) -> Result<GraphExportSummary, ModelError> {
+        let start_time = Instant::now();
+        info!(
+            "[GraphExporter - export] Exporting model {} version {} as {}",
+            model.model_id, model.version, format
+        );
+
+        let summary = match format {
+            GraphExportFormat::GraphMl => Self::write_graphml(model, attributes, writer),
+            GraphExportFormat::Jgf => Self::write_jgf(model, attributes, writer),
+        }
+        .map_err(|e| ModelError::ExportError(e.to_string()))?;
+
+        Utils::log_time(start_time, "GraphExporter - export");
+        Ok(summary)
+    }
+
+    pub fn write_graphml<W: Write>(
+        model: &ModelData,
+        attributes: &[FacetAttribute],
+        mut writer: W,
+    ) -> std::io::Result<GraphExportSummary> {
+        let mut summary = GraphExportSummary::default();
+
+        writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(
+            writer,
+            r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xsi:schemaLocation="http://graphml.graphdrawing.org/xmlns http://graphml.graphdrawing.org/xmlns/1.0/graphml.xsd">"#
+        )?;
+
+        // Keys
+        for target in ["node", "edge"] {
+            for field in ["type", "nature", "name", "version"] {
+                writeln!(
+                    writer,
+                    r#"  <key id="{}_{}" for="{}" attr.name="{}" attr.type="string"/>"#,
+                    target, field, target, field
+                )?;
+            }
+            for (i, attribute) in attributes.iter().enumerate() {
+                writeln!(
+                    writer,
+                    r#"  <key id="{}_facet{}" for="{}" attr.name="{}" attr.type="string"/>"#,
+                    target,
+                    i,
+                    target,
+                    escape_xml(&attribute.name)
+                )?;
+            }
+        }
+
+        writeln!(
+            writer,
+            r#"  <graph id="{}" edgedefault="directed">"#,
+            escape_xml(&model.model_id)
+        )?;
+
+        // Nodes
+        for element in &model.elements {
+            writeln!(writer, r#"    <node id="{}">"#, escape_xml(&element.id))?;
+            write_graphml_data(&mut writer, "node", element, element.version, attributes)?;
+            writeln!(writer, "    </node>")?;
+            summary.node_count += 1;
+        }
+
+        // Edges
+        for relationship in &model.relationships {
+            writeln!(
+                writer,
+                r#"    <edge id="{}" source="{}" target="{}">"#,
+                escape_xml(&relationship.id),
+                escape_xml(&relationship.source_id),
+                escape_xml(&relationship.target_id)
+            )?;
+            write_graphml_data(
+                &mut writer,
+                "edge",
+                relationship,
+                relationship.version,
+                attributes,
+            )?;
+            writeln!(writer, "    </edge>")?;
+            summary.edge_count += 1;
+        }
+
+        writeln!(writer, "  </graph>")?;
+        writeln!(writer, "</graphml>")?;
+        writer.flush()?;
+
+        Ok(summary)
+    }
+
+    pub fn write_jgf<W: Write>(
+        model: &ModelData,
+        attributes: &[FacetAttribute],
+        mut writer: W,
+    ) -> std::io::Result<GraphExportSummary> {
+        let mut summary = GraphExportSummary::default();
+
+        let graph_metadata = serde_json::json!({
+            "modelId": model.model_id,
+            "siteModelId": model.site_model_id,
+            "schemaVersion": model.schema_version,
+            "version": model.version,
+        });
+
+        write!(
+            writer,
+            r#"{{"graph":{{"id":{},"directed":true,"metadata":{},"nodes":{{"#,
+            Value::String(model.model_id.clone()),
+            graph_metadata
+        )?;
+
+        // Nodes
+        for (i, element) in model.elements.iter().enumerate() {
+            if i > 0 {
+                write!(writer, ",")?;
+            }
+            let node = serde_json::json!({
+                "label": element.name,
+                "metadata": jgf_metadata(element, element.version, attributes),
+            });
+            write!(writer, "{}:{}", Value::String(element.id.clone()), node)?;
+            summary.node_count += 1;
+        }
+
+        write!(writer, r#"}},"edges":["#)?;
+
+        // Edges
+        for (i, relationship) in model.relationships.iter().enumerate() {
+            if i > 0 {
+                write!(writer, ",")?;
+            }
+            let edge = serde_json::json!({
+                "id": relationship.id,
+                "source": relationship.source_id,
+                "target": relationship.target_id,
+                "relation": relationship.type_,
+                "directed": true,
+                "label": relationship.name,
+                "metadata": jgf_metadata(relationship, relationship.version, attributes),
+            });
+            write!(writer, "{}", edge)?;
+            summary.edge_count += 1;
+        }
+
+        writeln!(writer, "]}}}}")?;
+        writer.flush()?;
+
+        Ok(summary)
+    }
+}
+
+// Helper method
+fn write_graphml_data<W: Write, T: CusObject>(
+    writer: &mut W,
+    target: &str,
+    obj: &T,
+    version: u32,
+    attributes: &[FacetAttribute],
+) -> std::io::Result<()> {
+    let common_fields = [
+        ("type", obj.get_type()),
+        ("nature", obj.get_nature()),
+        ("name", obj.get_name()),
+        ("version", version.to_string()),
+    ];
+    for (field, value) in common_fields {
+        writeln!(
+            writer,
+            r#"      <data key="{}_{}">{}</data>"#,
+            target,
+            field,
+            escape_xml(&value)
+        )?;
+    }
+
+    for (i, attribute) in attributes.iter().enumerate() {
+        if let Some(value) = attribute.get_value(obj) {
+            writeln!(
+                writer,
+                r#"      <data key="{}_facet{}">{}</data>"#,
+                target,
+                i,
+                escape_xml(&value_to_attribute(&value))
+            )?;
+        }
+    }
+    Ok(())
+}
+
+fn jgf_metadata<T: CusObject>(obj: &T, version: u32, attributes: &[FacetAttribute]) -> Value {
+    let mut metadata = Map::new();
+    metadata.insert("type".to_string(), Value::String(obj.get_type()));
+    metadata.insert("nature".to_string(), Value::String(obj.get_nature()));
+    metadata.insert("version".to_string(), Value::from(version));
+
+    for attribute in attributes {
+        if let Some(value) = attribute.get_value(obj) {
+            metadata.insert(attribute.name.clone(), value);
+        }
+    }
+
+    Value::Object(metadata)
+}
+
+fn value_to_attribute(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+fn escape_xml(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            other => escaped.push(other),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::test_fixture;
+    use serde_json::json;
+
+    fn build_model() -> ModelData {
+        test_fixture::build_model(
+            3,
+            json!([
+                {"id": "e1", "type": "Pump", "nature": "Asset", "name": "P<1>", "version": 3,
+                 "facets": {"flow": {"rate": 12.5}}},
+                {"id": "e2", "type": "Tank", "nature": "Asset", "name": "T1", "version": 3,
+                 "facets": {}}
+            ]),
+            json!([
+                {"id": "r1", "sourceId": "e1", "targetId": "e2", "type": "feeds",
+                 "nature": "Flow", "version": 3, "facets": {"flow": {"rate": 4}}}
+            ]),
+        )
+    }
+
+    #[test]
+    fn test_parse_facet_attribute() {
+        let attribute = FacetAttribute::parse("facets:/flow/rate").unwrap();
+        assert_eq!(attribute.name, "facets/flow/rate");
+        assert_eq!(attribute.pointer, "/flow/rate");
+        assert!(FacetAttribute::parse("facets/flow").is_err());
+        assert!(FacetAttribute::parse("unknown:/flow").is_err());
+    }
+
+    #[test]
+    fn test_write_graphml() {
+        let model = build_model();
+        let attributes = FacetAttribute::parse_all(&["facets:/flow/rate".to_string()]).unwrap();
+        let mut buffer: Vec<u8> = Vec::new();
+        let summary = GraphExporter::write_graphml(&model, &attributes, &mut buffer).unwrap();
+        let output = String::from_utf8(buffer).unwrap();
+
+        assert_eq!(summary.node_count, 2);
+        assert_eq!(summary.edge_count, 1);
+        assert!(output.contains(r#"<node id="e1">"#));
+        assert!(output.contains(r#"<data key="node_name">P&lt;1&gt;</data>"#));
+        assert!(output.contains(r#"<data key="node_facet0">12.5</data>"#));
+        assert!(output.contains(r#"<edge id="r1" source="e1" target="e2">"#));
+        assert!(output.contains(r#"<data key="edge_facet0">4</data>"#));
+    }
+
+    #[test]
+    fn test_write_jgf() {
+        let model = build_model();
+        let attributes = FacetAttribute::parse_all(&["facets:/flow/rate".to_string()]).unwrap();
+        let mut buffer: Vec<u8> = Vec::new();
+        GraphExporter::write_jgf(&model, &attributes, &mut buffer).unwrap();
+        let output: Value = serde_json::from_slice(&buffer).unwrap();
+
+        assert_eq!(output["graph"]["nodes"]["e1"]["label"], "P<1>");
+        assert_eq!(
+            output["graph"]["nodes"]["e1"]["metadata"]["facets/flow/rate"],
+            12.5
+        );
+        assert_eq!(output["graph"]["edges"][0]["relation"], "feeds");
+        assert_eq!(output["graph"]["edges"][0]["source"], "e1");
+    }
+}
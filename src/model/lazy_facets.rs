@@ -0,0 +1,134 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, OnceLock};
+
+use serde::de::{self, Unexpected};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+use serde_json::value::RawValue;
+
+use crate::model::size_estimate::{EstimateSize, map_size};
+
+// Element facet map kept as raw json until a query reads it.
+// Eager snapshots parse every map once at build time and drop the raw json,
+// lazy snapshots parse a map on first read and keep both afterwards.
+
+#[derive(Debug, Clone, Default)]
+pub struct LazyFacets {
+    raw: Option<Box<RawValue>>,
+    parsed: OnceLock<HashMap<String, Value>>,
+    // Heap of maps parsed on read, shared by the facets of one snapshot
+    parsed_size: Option<Arc<AtomicUsize>>,
+}
+
+impl LazyFacets {
+    pub fn from_map(map: HashMap<String, Value>) -> Self {
+        Self {
+            raw: None,
+            parsed: OnceLock::from(map),
+            parsed_size: None,
+        }
+    }
+
+    pub fn get(&self) -> &HashMap<String, Value> {
+        self.parsed.get_or_init(|| {
+            let map: HashMap<String, Value> =
+                self.raw.as_deref().map(Self::parse_raw).unwrap_or_default();
+            if let Some(parsed_size) = &self.parsed_size {
+                parsed_size.fetch_add(map_size(&map), Ordering::Relaxed);
+            }
+            map
+        })
+    }
+
+    // Add the size of the map parsed by a later read to parsed_size
+    pub fn track_parsed_size(&mut self, parsed_size: &Arc<AtomicUsize>) {
+        if !self.is_parsed() {
+            self.parsed_size = Some(Arc::clone(parsed_size));
+        }
+    }
+
+    pub fn is_parsed(&self) -> bool {
+        self.parsed.get().is_some()
+    }
+
+    // Parse now and free the raw json
+    pub fn parse(&mut self) {
+        if let Some(raw) = self.raw.take() {
+            self.parsed.get_or_init(|| Self::parse_raw(&raw));
+        }
+    }
+
+    // Checked to be an object when deserialized, any object is a valid map
+    fn parse_raw(raw: &RawValue) -> HashMap<String, Value> {
+        serde_json::from_str(raw.get()).unwrap_or_default()
+    }
+}
+
+impl EstimateSize for LazyFacets {
+    fn estimate_size(&self) -> usize {
+        self.raw.as_ref().map_or(0, |raw| raw.get().len())
+            + self.parsed.get().map_or(0, map_size)
+    }
+}
+
+impl Serialize for LazyFacets {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        // Raw json is written as is, no parsing for full output
+        match (self.parsed.get(), self.raw.as_deref()) {
+            (Some(map), _) => map.serialize(serializer),
+            (None, Some(raw)) => raw.serialize(serializer),
+            (None, None) => HashMap::<String, Value>::new().serialize(serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for LazyFacets {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = Box::<RawValue>::deserialize(deserializer)?;
+        // Only the shape is checked here so a bad model still fails to load
+        if !raw.get().starts_with('{') {
+            return Err(de::Error::invalid_type(
+                Unexpected::Other(raw.get()),
+                &"a map of facets",
+            ));
+        }
+        Ok(Self {
+            raw: Some(raw),
+            parsed: OnceLock::new(),
+            parsed_size: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_lazy_parse() {
+        let mut facets: LazyFacets =
+            serde_json::from_str(r#"{"flowRate": {"value": 12.5}}"#).unwrap();
+        assert!(!facets.is_parsed());
+        // Serialized without parsing
+        assert_eq!(
+            serde_json::to_value(&facets).unwrap(),
+            json!({"flowRate": {"value": 12.5}})
+        );
+        assert!(!facets.is_parsed());
+
+        assert_eq!(facets.get()["flowRate"]["value"], json!(12.5));
+        assert!(facets.is_parsed());
+        facets.parse();
+        assert!(facets.raw.is_none());
+        assert_eq!(facets.get().len(), 1);
+    }
+
+    #[test]
+    fn test_not_a_map() {
+        assert!(serde_json::from_str::<LazyFacets>("[1, 2]").is_err());
+        assert!(serde_json::from_str::<LazyFacets>("null").is_err());
+        assert!(serde_json::from_value::<LazyFacets>(json!({"a": 1})).is_ok());
+    }
+}
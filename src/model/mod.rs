@@ -1,12 +1,30 @@
 pub mod app_state;
+pub mod cache_admin;
+pub mod cache_warmer;
 pub mod config;
 pub mod cubs_model;
 mod database_util;
-mod element_graph;
-mod element_graph_parser;
+pub mod element_graph;
+pub mod element_graph_ancestor;
+pub mod element_graph_centrality;
+pub mod element_graph_impact;
+pub mod element_graph_layering;
+pub mod element_graph_neighbour;
+pub mod element_graph_parser;
 mod element_parser;
-mod model_dict;
-mod model_error;
+pub mod graph_export;
+pub mod lazy_facets;
+pub mod model_dict;
+pub mod model_error;
+pub mod model_migration;
 pub mod model_parser;
+pub mod model_snapshot;
+pub mod model_source;
+pub mod model_validator;
+pub mod output_renderer;
+pub mod pattern_query;
 mod parser;
-mod utils;
+pub mod size_estimate;
+#[cfg(test)]
+mod test_fixture;
+pub mod utils;
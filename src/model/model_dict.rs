@@ -4,6 +4,7 @@ use std::collections::HashMap;
 use std::time::Instant;
 
 use super::cubs_model::ModelData;
+use crate::info;
 use crate::model::cubs_model::{CusObject, Element, ModelVersionNumber, Relationship};
 
 #[derive(Debug, Serialize)]
@@ -68,9 +69,8 @@ impl ModelDictionary {
 
         //Log time
         let elapsed_time = start_time.elapsed();
-        println!(
-            "[Execution time] {} - {:?}",
-            "ModelDictionary::from", elapsed_time
+        info!(
+            "[Execution time] ModelDictionary::from - {:?}", elapsed_time
         );
 
         // Construct output
@@ -91,7 +91,7 @@ impl ModelDictionary {
                 }),
                 version: model.version,
             },
-            model_versions: model_versions,
+            model_versions,
         }
     }
 
@@ -123,8 +123,8 @@ impl ModelStats {
         let count = elements.len() as u32;
 
         for element in elements {
-            *by_type.entry(element.type_.clone()).or_insert(0 as u32) += 1;
-            *by_nature.entry(element.nature.clone()).or_insert(0 as u32) += 1;
+            *by_type.entry(element.type_.clone()).or_insert(0_u32) += 1;
+            *by_nature.entry(element.nature.clone()).or_insert(0_u32) += 1;
         }
 
         // Construct Output
@@ -132,13 +132,13 @@ impl ModelStats {
             .into_iter()
             .map(|(element, count)| ElementCount { element, count })
             .collect();
-        element_count_by_type.sort_by(|a, b| b.count.cmp(&a.count));
+        element_count_by_type.sort_by_key(|c| std::cmp::Reverse(c.count));
 
         let mut element_count_by_nature: Vec<ElementCount> = by_nature
             .into_iter()
             .map(|(element, count)| ElementCount { element, count })
             .collect();
-        element_count_by_nature.sort_by(|a, b| b.count.cmp(&a.count));
+        element_count_by_nature.sort_by_key(|c| std::cmp::Reverse(c.count));
 
         Some(Self {
             elements_stats: Some(CubsObjectReport {
@@ -163,11 +163,10 @@ pub fn generate_array_field_count(value: &Value, field_name: &str) -> Option<Ele
     let mut type_counts: HashMap<String, u32> = HashMap::new();
 
     for element in array {
-        if let Some(type_value) = element.get(field_name) {
-            if let Some(type_str) = type_value.as_str() {
+        if let Some(type_value) = element.get(field_name)
+            && let Some(type_str) = type_value.as_str() {
                 *type_counts.entry(type_str.to_owned()).or_insert(0) += 1;
             }
-        }
     }
 
     if type_counts.is_empty() {
@@ -178,7 +177,7 @@ pub fn generate_array_field_count(value: &Value, field_name: &str) -> Option<Ele
         .into_iter()
         .map(|(element, count)| ElementCount { element, count })
         .collect();
-    counts.sort_by(|a, b| b.count.cmp(&a.count));
+    counts.sort_by_key(|c| std::cmp::Reverse(c.count));
 
     Some(ElementCounts { value: counts })
 }
@@ -192,7 +191,7 @@ fn _get_json_array_len(value: &Value) -> u32 {
 }
 
 pub fn generate_element_count_by<T, F>(
-    cubs_objects: &Vec<T>,
+    cubs_objects: &[T],
     key_getter: F,
 ) -> Option<ElementCounts>
 where
@@ -216,7 +215,7 @@ where
         .into_iter()
         .map(|(element, count)| ElementCount { element, count })
         .collect();
-    element_counts.sort_by(|a, b| b.count.cmp(&a.count));
+    element_counts.sort_by_key(|c| std::cmp::Reverse(c.count));
 
     Some(ElementCounts {
         value: element_counts,
@@ -11,6 +11,8 @@ pub enum ModelError {
     UnableToReadModel,
     InvalidInput(String),
     ParsingError(String),
+    ExportError(String),
+    SourceError(String),
 }
 
 impl fmt::Display for ModelError {
@@ -25,6 +27,8 @@ impl fmt::Display for ModelError {
             ModelError::InvalidInput(err) => write!(f, "Invalid input, {}",err),
             ModelError::ParsingError(err) => write!(f, "Unable to parse {}", err),
             ModelError::UnableToReadModel => write!(f, "Unable to read model"),
+            ModelError::ExportError(err) => write!(f, "Unable to export model, {}", err),
+            ModelError::SourceError(err) => write!(f, "Unable to read model source, {}", err),
         }
     }
 }
@@ -0,0 +1,109 @@
+use serde_json::{Map, Value};
+
+use crate::model::{cubs_model::ModelData, model_error::ModelError};
+
+// Saved model layouts older than the current ModelData shape, upgraded step by step.
+// Stored schemaVersion values are not tied to a layout and are kept as is, a layout is
+// recognised by a top level key the current layout does not have.
+//
+// cubsObjects: elements under cubsObjects, relationships and schemaVersion may be missing
+
+// Upgrade a model value from its layout to the next one
+type Migration = fn(Map<String, Value>) -> Result<Map<String, Value>, ModelError>;
+
+// Top level key marking the layout : its migration, oldest first
+static MIGRATIONS: [(&str, Migration); 1] = [("cubsObjects", migrate_cubs_objects)];
+
+pub struct ModelMigration;
+
+impl ModelMigration {
+    // True when one of the top level keys marks an older layout
+    pub fn is_legacy_layout<'k>(keys: impl IntoIterator<Item = &'k str>) -> bool {
+        keys.into_iter()
+            .any(|key| MIGRATIONS.iter().any(|(layout_key, _)| *layout_key == key))
+    }
+
+    // Upgrade an older layout into the current ModelData
+    pub fn migrate(value: Value) -> Result<ModelData, ModelError> {
+        let Value::Object(mut model) = value else {
+            return Err(ModelError::ParsingError(
+                "model data, expected a JSON object".to_string(),
+            ));
+        };
+
+        for (layout_key, migration) in MIGRATIONS.iter() {
+            if model.contains_key(*layout_key) {
+                model = migration(model)?;
+            }
+        }
+
+        // Schema version is only reported
+        let schema_version = match model.remove("schemaVersion") {
+            None | Some(Value::Null) => String::new(),
+            Some(Value::String(s)) => s,
+            Some(other) => other.to_string(),
+        };
+        model.insert("schemaVersion".to_string(), Value::String(schema_version));
+        serde_json::from_value(Value::Object(model))
+            .map_err(|e| ModelError::ParsingError(format!("model data, {}", e)))
+    }
+}
+
+fn migrate_cubs_objects(mut model: Map<String, Value>) -> Result<Map<String, Value>, ModelError> {
+    if let Some(elements) = model.remove("cubsObjects") {
+        model.entry("elements").or_insert(elements);
+    }
+    model
+        .entry("relationships")
+        .or_insert(Value::Array(Vec::new()));
+    Ok(model)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_is_legacy_layout() {
+        assert!(ModelMigration::is_legacy_layout(["modelId", "cubsObjects"]));
+        assert!(!ModelMigration::is_legacy_layout([
+            "schemaVersion",
+            "modelId",
+            "elements",
+            "relationships"
+        ]));
+    }
+
+    #[test]
+    fn test_migrate_cubs_objects() {
+        let model_data = ModelMigration::migrate(json!({
+            "modelId": "m1",
+            "siteModelId": "s1",
+            "version": 2,
+            "cubsObjects": [
+                {"id": "p1", "type": "Pump", "nature": "Asset", "name": "P1", "version": 1, "facets": {}}
+            ]
+        }))
+        .unwrap();
+        assert_eq!(model_data.schema_version, "");
+        assert_eq!(model_data.elements[0].id, "p1");
+        assert!(model_data.relationships.is_empty());
+
+        // Stored schema version is kept whatever its value
+        let model_data = ModelMigration::migrate(json!({
+            "schemaVersion": "7",
+            "modelId": "m1",
+            "siteModelId": "s1",
+            "version": 2,
+            "cubsObjects": []
+        }))
+        .unwrap();
+        assert_eq!(model_data.schema_version, "7");
+
+        assert!(matches!(
+            ModelMigration::migrate(json!([])),
+            Err(ModelError::ParsingError(_))
+        ));
+    }
+}
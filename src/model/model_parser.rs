@@ -1,24 +1,42 @@
-use serde_json::Value;
-use sqlx::{Pool, Postgres};
-use std::{fmt::Display, sync::Arc, time::Instant};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::{sync::Arc, time::Instant};
 
+use crate::info;
 use crate::model::{
     app_state::QuickCache,
-    config::PageConfig,
-    cubs_model::{self, Element, FacetType, ModelData, ModelVersionNumber},
-    element_graph::ElementGraph,
+    config::{OutputGraph, PageConfig, TraversalDirection},
+    cubs_model::{
+        self, Element, ElementSummary, FacetType, ModelData, ModelVersionNumber, Relationship,
+    },
+    element_graph::Path,
+    element_graph_ancestor::{AncestorDistance, ElementGraphAncestor},
+    element_graph_centrality::{CentralityMetric, CentralityReport, ElementGraphCentrality},
+    element_graph_impact::{ElementGraphImpact, ImpactedElement},
+    element_graph_layering::{CycleStep, ElementGraphLayering, TopologicalOrder},
+    element_graph_neighbour::ElementGraphNeighbour,
     element_graph_parser::ElementGraphParser,
-    element_parser::ElementConnectorBuilder,
+    graph_export::{FacetAttribute, GraphExportFormat, GraphExportSummary, GraphExporter},
     model_dict::{ModelDictionary, ModelStats},
     model_error::ModelError,
+    model_snapshot::{ModelSnapshot, SnapshotMode},
+    model_source::{ModelRange, ModelSource, SavedModelPage, validate_model_id},
+    model_validator::{ModelValidator, Severity, ValidationFinding},
     parser,
+    pattern_query::{PatternMatcher, PatternQuery},
     utils::Utils,
 };
 
+static ALL: &str = "All";
+static MAX_PATTERN_ROWS: usize = 10000;
+static MAX_LINEAGE_DEPTH: u32 = 20;
+static MAX_NEIGHBOUR_HOP: usize = 20;
+
 pub struct ModelParser<'a> {
-    model_cache: QuickCache<ModelData>,
-    graph_cache: QuickCache<ElementGraph>,
-    pg_pool: &'a Pool<Postgres>,
+    snapshot_cache: QuickCache<ModelSnapshot>,
+    centrality_cache: QuickCache<CentralityReport>,
+    model_source: &'a dyn ModelSource,
+    snapshot_mode: SnapshotMode,
 }
 
 #[derive(Default, Debug)]
@@ -30,6 +48,178 @@ pub struct ModelQueryResult {
     pub stats: Option<ModelStats>,
 }
 
+#[derive(Debug, Serialize)]
+pub struct ModelExportResult {
+    pub model_id: String,
+    pub version: u32,
+    pub format: String,
+    pub file_path: String,
+    pub summary: GraphExportSummary,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ElementCentralityResult {
+    pub id: String,
+    pub name: String,
+    #[serde(rename = "type")]
+    pub type_: String,
+    pub nature: String,
+    pub score: f64,
+    pub in_degree: usize,
+    pub out_degree: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub betweenness: Option<f64>,
+    pub pagerank: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ElementLayer {
+    pub layer: usize,
+    pub elements: Vec<ElementSummary>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ModelLayeringResult {
+    pub is_acyclic: bool,
+    pub total_layers: usize,
+    pub total_result_count: usize,
+    pub elements_per_page: usize,
+    pub total_page: usize,
+    pub current_page: usize,
+    pub layers: Vec<ElementLayer>,
+    pub cycle: Vec<CycleStep>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CommonAncestorResult {
+    pub ancestor: ElementSummary,
+    pub is_lowest: bool,
+    pub distances: Vec<AncestorDistance>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImpactStepResult {
+    pub relationship_id: String,
+    pub relationship_type: String,
+    pub source_id: String,
+    pub target_id: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImpactedElementResult {
+    pub element: ElementSummary,
+    pub depth: usize,
+    pub path: Vec<ImpactStepResult>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImpactGroup {
+    #[serde(rename = "type")]
+    pub type_: String,
+    pub elements: Vec<ImpactedElementResult>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ModelImpactResult {
+    pub element_id: String,
+    pub total_result_count: usize,
+    pub elements_per_page: usize,
+    pub total_page: usize,
+    pub current_page: usize,
+    pub stats: Option<ModelStats>,
+    pub groups: Vec<ImpactGroup>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ModelPatternResult {
+    pub variables: Vec<String>,
+    pub total_result_count: usize,
+    // True when the search stopped at the row limit
+    pub is_truncated: bool,
+    pub elements_per_page: usize,
+    pub total_page: usize,
+    pub current_page: usize,
+    pub rows: Vec<Map<String, Value>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RelationshipSummary {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub type_: String,
+    pub nature: String,
+    pub name: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NeighbourResult {
+    pub element: ElementSummary,
+    pub hop: usize,
+    pub direction: TraversalDirection,
+    pub via_element_id: String,
+    pub relationship: RelationshipSummary,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ModelNeighbourResult {
+    pub element_id: String,
+    pub total_result_count: usize,
+    pub elements_per_page: usize,
+    pub total_page: usize,
+    pub current_page: usize,
+    pub neighbours: Vec<NeighbourResult>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelListEntry {
+    pub model_id: String,
+    pub version_count: usize,
+    pub latest_version: i32,
+    // Only known once the latest version is loaded
+    pub site_model_id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ModelListResult {
+    pub total_result_count: usize,
+    pub elements_per_page: usize,
+    pub total_page: usize,
+    pub current_page: usize,
+    pub models: Vec<ModelListEntry>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ValidationGroup {
+    pub severity: Severity,
+    pub findings: Vec<ValidationFinding>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ModelValidationResult {
+    pub model_id: String,
+    pub version: u32,
+    // True when there is no error, warnings are allowed
+    pub is_valid: bool,
+    pub error_count: usize,
+    pub warning_count: usize,
+    pub total_result_count: usize,
+    pub elements_per_page: usize,
+    pub total_page: usize,
+    pub current_page: usize,
+    pub groups: Vec<ValidationGroup>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ModelRelationshipFacetResult {
+    pub model_id: String,
+    pub version: u32,
+    pub total_result_count: usize,
+    pub elements_per_page: usize,
+    pub total_page: usize,
+    pub current_page: usize,
+    pub relationships: Vec<Value>,
+}
+
 #[derive(Default, Debug)]
 pub struct Page {
     pub elements_per_page: usize,
@@ -39,17 +229,24 @@ pub struct Page {
 
 impl<'a> ModelParser<'a> {
     pub fn new(
-        model_cache: QuickCache<ModelData>,
-        graph_cache: QuickCache<ElementGraph>,
-        pg_pool: &'a Pool<Postgres>,
+        snapshot_cache: QuickCache<ModelSnapshot>,
+        centrality_cache: QuickCache<CentralityReport>,
+        model_source: &'a dyn ModelSource,
     ) -> Self {
         ModelParser {
-            model_cache,
-            graph_cache,
-            pg_pool,
+            snapshot_cache,
+            centrality_cache,
+            model_source,
+            snapshot_mode: SnapshotMode::default(),
         }
     }
 
+    // Mode used by the snapshots built by this parser
+    pub fn with_snapshot_mode(mut self, snapshot_mode: SnapshotMode) -> Self {
+        self.snapshot_mode = snapshot_mode;
+        self
+    }
+
     //Get model Stats
     pub async fn get_model_stats(
         &self,
@@ -57,14 +254,14 @@ impl<'a> ModelParser<'a> {
         version_number: &str,
     ) -> Result<ModelDictionary, ModelError> {
         let model_id = model_id.to_owned();
-        println!(
+        info!(
             "[ModelParser - get_model_stats] Getting model stats of {} with version {}",
             model_id, version_number
         );
         let start_time = Instant::now();
 
-        //Read all model version
-        let model_version = parser::read_model_data_versions(self.pg_pool, &model_id)
+        //Read all model version with their details
+        let model_version = parser::read_model_data_version_details(self.model_source, &model_id)
             .await
             .unwrap_or_default();
         Utils::log_time(start_time, "Read model data version");
@@ -73,15 +270,33 @@ impl<'a> ModelParser<'a> {
         let version_number = ModelParser::get_version_number(version_number, &model_version);
 
         // Get model
-        let model_data = self.get_model_ref(&model_id, version_number).await?;
+        let snapshot = self
+            .get_snapshot_with_version(&model_id, version_number)
+            .await?;
 
-        // Build relationship
-        self.build_relationship_graph(&model_id, model_data.version, &model_data);
+        // Schema version of loaded versions when the source does not record it
+        let model_version = model_version
+            .into_iter()
+            .map(|mut v| {
+                if v.schema_version.is_none() {
+                    v.schema_version = self
+                        .snapshot_cache
+                        .peek_ref(&model_id, &v.vers_no.to_string())
+                        .map(|s| s.get_model_data().schema_version.clone());
+                }
+                v
+            })
+            .collect();
 
         // Build dict
-        let dict = ModelDictionary::from(&model_data, model_version);
+        let dict = ModelDictionary {
+            model_id: snapshot.get_model_id().to_owned(),
+            version: snapshot.get_version(),
+            model_stats: snapshot.get_stats().clone(),
+            model_versions: model_version,
+        };
         Utils::log_time(start_time, "Get model stats");
-        println!(
+        info!(
             "[get_model_stats_ref] Successfully parse model with id {} \n",
             model_id
         );
@@ -90,6 +305,7 @@ impl<'a> ModelParser<'a> {
     }
 
     //Query model
+    #[allow(clippy::too_many_arguments)]
     pub async fn query_model(
         &self,
         model_id: String,
@@ -104,7 +320,7 @@ impl<'a> ModelParser<'a> {
         facet_type: String,
         is_detail: bool,
     ) -> Result<ModelQueryResult, ModelError> {
-        println!(
+        info!(
             "[ModelParser - query_model] model_id: {}, version_number: {}, id: {}, is_parse_subgraph: {}, types: {}, natures: {}, query: {}, depth: {}, page_config: {:?}, facet_type: {}, is_detail: {}",
             model_id,
             version_number,
@@ -135,11 +351,7 @@ impl<'a> ModelParser<'a> {
         let start_time = Instant::now();
 
         // Get model
-        let model_version = parser::read_model_data_versions(self.pg_pool, &model_id)
-            .await
-            .unwrap_or_default();
-        let i_version_number = ModelParser::get_version_number(&version_number, &model_version);
-        let model_data = self.get_model_ref(&model_id, i_version_number).await?;
+        let snapshot = self.get_snapshot(&model_id, &version_number).await?;
         Utils::log_time(start_time, "Read model data");
 
         // Perform Filtering
@@ -147,64 +359,45 @@ impl<'a> ModelParser<'a> {
 
         // Get subgraph
         let subgraph_elements: Vec<String> = if is_parse_subgraph && !id.is_empty() {
-            let graph_cache = &self.graph_cache;
-            let graph = if let Some(graph) = graph_cache.get_ref(&model_id, &version_number) {
-                graph
-            } else {
-                // Build graph if not found
-                let built_graph = ElementConnectorBuilder::build_graph(
-                    &model_data.elements,
-                    &model_data.relationships,
-                )?;
-
-                // Add to cache
-                graph_cache.insert(&model_id, &version_number, &built_graph);
-
-                // Get referance
-                graph_cache.get_ref(&model_id, &version_number).unwrap()
-            };
-
-            ElementGraphParser::parse_graph(&graph, &id, 0, 99)
+            let graph = snapshot.get_graph()?;
+            ElementGraphParser::parse_graph(graph, &id, 0, 99)
                 .map(|g| g.get_all_elements())
-                .unwrap_or(Vec::new())
+                .unwrap_or_default()
         } else {
             Vec::new()
         };
 
         //Filter id
+        let type_filter = (types != ALL).then_some(types.as_str());
+        let nature_filter = (natures != ALL).then_some(natures.as_str());
         let mut filtered_elements = if id.is_empty() {
-            model_data.get_elements()
+            // Type and nature from index
+            snapshot.get_elements_with_type_nature(type_filter, nature_filter)
         } else if is_parse_subgraph && !subgraph_elements.is_empty() {
             //Parsing subgraph
-            println!("[ModelParser - query_model] - Filtering sub graph");
-            model_data.get_element_with_filter(|e: &Element| subgraph_elements.contains(&e.id))
+            info!("[ModelParser - query_model] - Filtering sub graph");
+            snapshot.get_elements_with_ids(&subgraph_elements)
         } else {
             //Filter id only
-            println!("[ModelParser - query_model] - Filtering id");
-            model_data
-                .get_element_with_id(&id)
+            info!("[ModelParser - query_model] - Filtering id");
+            snapshot
+                .get_element(&id)
                 .map(|e| vec![e])
-                .unwrap_or_else(|| Vec::new())
+                .unwrap_or_else(Vec::new)
         };
 
-        println!(
+        info!(
             "[ModelParser - query_model] Pre-filter element count {} ",
             filtered_elements.len()
         );
 
         //filter nature
-        filtered_elements.retain(|e| match natures.as_str() {
-            "All" => true,
-            _ => *e.nature == natures,
-        });
+        filtered_elements.retain(|e| nature_filter.is_none_or(|n| e.nature == n));
 
         //filter type
-        filtered_elements.retain(|e| match types.as_str() {
-            "All" => true,
-            _ => *e.type_ == types,
-        });
+        filtered_elements.retain(|e| type_filter.is_none_or(|t| e.type_ == t));
         Utils::log_time(filtering_start_time, "Filtering model data");
-        println!(
+        info!(
             "[ModelParser - query_model] {} elements after filtered",
             filtered_elements.len()
         );
@@ -214,13 +407,8 @@ impl<'a> ModelParser<'a> {
 
         //Apply json pointer
         let json_pointer_start_time = Instant::now();
-        let facet_type: Option<FacetType> = match facet_type.as_str() {
-            "dynamicFacets" => Some(FacetType::DynamicFacets),
-            "coreFacets" => Some(FacetType::CoreFacets),
-            "facets" => Some(FacetType::Facets),
-            _ => None,
-        };
-        println!(
+        let facet_type: Option<FacetType> = FacetType::parse(&facet_type);
+        info!(
             "[ModelParser - query_model] Applying json pointer facet type: {:?} pointer: {} with detail: {}",
             facet_type, &query, is_detail
         );
@@ -235,32 +423,25 @@ impl<'a> ModelParser<'a> {
         };
         let filtered_element_len = filtered_elements.len();
         Utils::log_time(json_pointer_start_time, "Apply json pointer model data");
+        self.reweigh_snapshot(&snapshot);
 
         //Limit & Pagination
         let limittation_and_pagination_start_time = Instant::now();
-        let elements_chunks: Vec<&[Value]> = filtered_elements
-            .chunks(page_config.elements_per_page)
-            .collect();
-        let page = Page {
-            elements_per_page: page_config.elements_per_page,
-            total_page: elements_chunks.len(),
-            current_page: page_config.page_to_get,
-        };
-        let limited_query_result = elements_chunks[page_config.page_to_get - 1]; //BUG handle no result found
-        println!(
+        let (page, limited_query_result) = ModelParser::paginate(&filtered_elements, &page_config);
+        info!(
             "[ModelParser - query_model] Getting page {} of {} with total element {}",
             page.current_page, page.total_page, filtered_element_len
         );
 
         //Depth
-        println!(
+        info!(
             "[ModelParser - query_model] truncating {} elements to depth {}",
             limited_query_result.len(),
             depth
         );
         let elements = match depth > 0 {
             true => {
-                let filtered_element = cubs_model::truncate_value(&limited_query_result, depth);
+                let filtered_element = cubs_model::truncate_value(limited_query_result, depth);
                 serde_json::to_string_pretty(&filtered_element).unwrap()
             }
             false => serde_json::to_string_pretty(&limited_query_result).unwrap(),
@@ -279,86 +460,890 @@ impl<'a> ModelParser<'a> {
             data: elements,
             duration: format!(
                 "Query model took {} ms",
-                elapsed_time.as_millis().to_string()
+                elapsed_time.as_millis()
             ),
             page_count: page,
             total_result_count: filtered_elements.len(),
-            stats: stats,
+            stats,
         })
     }
 
-    async fn get_model_ref(
+    //Export model graph
+    pub async fn export_model_graph(
         &self,
-        model_id: &String,
-        version_number: i32,
-    ) -> Result<Arc<ModelData>, ModelError> {
-        // Get from cache else from DB
-        let model_data =
-            match parser::get_model_from_cache(&self.model_cache, &model_id, version_number) {
-                Some(cached_model) => cached_model,
-                None => {
-                    let cached_model = parser::get_model_from_db(
-                        self.pg_pool,
-                        &self.model_cache,
-                        &model_id,
-                        version_number,
-                    )
-                    .await
-                    .ok_or_else(|| {
-                        println!(
-                "[get_model_stats_ref] model id {} not found or having issue retrieve model",
-                model_id
-            );
-                        ModelError::ModelNotFound(model_id.clone(), version_number.to_string())
-                    })?;
-
-                    cached_model
+        model_id: &str,
+        version_number: &str,
+        format: GraphExportFormat,
+        attributes: Vec<FacetAttribute>,
+        output_dir: &str,
+    ) -> Result<ModelExportResult, ModelError> {
+        info!(
+            "[ModelParser - export_model_graph] Exporting {} with version {} as {}",
+            model_id, version_number, format
+        );
+        let start_time = Instant::now();
+
+        // Model id is part of the file name
+        validate_model_id(model_id)?;
+
+        // Get model
+        let model_id = model_id.to_owned();
+        let snapshot = self.get_snapshot(&model_id, version_number).await?;
+        let version = snapshot.get_version();
+
+        // Stream into file
+        let file_path = std::path::Path::new(output_dir).join(format!(
+            "{}_{}.{}",
+            model_id,
+            version,
+            format.file_extension()
+        ));
+        let output_path = file_path.clone();
+        let exported_snapshot = Arc::clone(&snapshot);
+        let summary = tokio::task::spawn_blocking(move || {
+            std::fs::create_dir_all(output_path.parent().unwrap_or(std::path::Path::new(".")))
+                .map_err(|e| ModelError::ExportError(e.to_string()))?;
+            let file = std::fs::File::create(&output_path)
+                .map_err(|e| ModelError::ExportError(e.to_string()))?;
+            GraphExporter::export(
+                exported_snapshot.get_model_data(),
+                format,
+                &attributes,
+                std::io::BufWriter::new(file),
+            )
+        })
+        .await
+        .map_err(|e| ModelError::ExportError(e.to_string()))??;
+        self.reweigh_snapshot(&snapshot);
+        Utils::log_time(start_time, "ModelParser - export_model_graph");
+
+        Ok(ModelExportResult {
+            model_id,
+            version,
+            format: format.to_string(),
+            file_path: file_path.to_string_lossy().to_string(),
+            summary,
+        })
+    }
+
+    //Get most central elements
+    pub async fn get_element_centrality(
+        &self,
+        model_id: &str,
+        version_number: &str,
+        metric: CentralityMetric,
+        top_n: usize,
+        types: &str,
+    ) -> Result<Vec<ElementCentralityResult>, ModelError> {
+        info!(
+            "[ModelParser - get_element_centrality] model_id: {}, version_number: {}, metric: {:?}, top_n: {}, types: {}",
+            model_id, version_number, metric, top_n, types
+        );
+        let start_time = Instant::now();
+        let snapshot = self.get_snapshot(model_id, version_number).await?;
+
+        // Get from cache else compute, again when betweenness was left out
+        let version = snapshot.get_version().to_string();
+        let cached_report = self
+            .centrality_cache
+            .get_ref(model_id, &version)
+            .filter(|report| report.has_metric(metric));
+        let report = match cached_report {
+            Some(report) => report,
+            None => {
+                let graph_snapshot = Arc::clone(&snapshot);
+                let report = tokio::task::spawn_blocking(move || {
+                    graph_snapshot
+                        .get_graph()
+                        .map(|graph| ElementGraphCentrality::compute(graph, metric))
+                })
+                .await
+                .map_err(|e| ModelError::ModelGraphBuildingError(e.to_string()))??;
+                let report = Arc::new(report);
+                self.centrality_cache
+                    .insert_arc(model_id, &version, Arc::clone(&report));
+                report
+            }
+        };
+
+        // Rank with type filter
+        let result = report
+            .top(metric, top_n, |s| {
+                types == ALL
+                    || snapshot
+                        .get_element(&s.element_id)
+                        .is_some_and(|e| e.type_ == types)
+            })
+            .into_iter()
+            .filter_map(|s| {
+                snapshot
+                    .get_element(&s.element_id)
+                    .map(|e| ElementCentralityResult {
+                        id: e.id.clone(),
+                        name: e.name.clone(),
+                        type_: e.type_.clone(),
+                        nature: e.nature.clone(),
+                        score: s.get_score(metric),
+                        in_degree: s.in_degree,
+                        out_degree: s.out_degree,
+                        betweenness: s.betweenness,
+                        pagerank: s.pagerank,
+                    })
+            })
+            .collect();
+        Utils::log_time(start_time, "ModelParser - get_element_centrality");
+
+        Ok(result)
+    }
+
+    //Get topological layers
+    pub async fn get_topological_layers(
+        &self,
+        model_id: &str,
+        version_number: &str,
+        relationship_types: &[String],
+        include_isolated: bool,
+        page_config: PageConfig,
+    ) -> Result<ModelLayeringResult, ModelError> {
+        info!(
+            "[ModelParser - get_topological_layers] model_id: {}, version_number: {}, relationship_types: {:?}, include_isolated: {}, page_config: {:?}",
+            model_id, version_number, relationship_types, include_isolated, page_config
+        );
+        let start_time = Instant::now();
+        let snapshot = self.get_snapshot(model_id, version_number).await?;
+        let graph = snapshot.get_graph()?;
+
+        // Select relationship by type
+        let order = ElementGraphLayering::compute(
+            graph,
+            |path| ModelParser::is_relationship_type_selected(relationship_types, path),
+            include_isolated,
+        );
+
+        let layers = match order {
+            TopologicalOrder::Layers(layers) => layers,
+            TopologicalOrder::Cycle(cycle) => {
+                return Ok(ModelLayeringResult {
+                    is_acyclic: false,
+                    total_layers: 0,
+                    total_result_count: 0,
+                    elements_per_page: page_config.elements_per_page,
+                    total_page: 0,
+                    current_page: page_config.page_to_get,
+                    layers: Vec::new(),
+                    cycle,
+                });
+            }
+        };
+
+        // Paginate over elements ordered by layer
+        let ordered: Vec<(usize, &String)> = layers
+            .iter()
+            .enumerate()
+            .flat_map(|(layer, ids)| ids.iter().map(move |id| (layer, id)))
+            .collect();
+        let (page, limited) = ModelParser::paginate(&ordered, &page_config);
+
+        let mut page_layers: Vec<ElementLayer> = Vec::new();
+        for (layer, id) in limited {
+            let Some(element) = snapshot.get_element(id) else {
+                continue;
+            };
+            match page_layers.last_mut() {
+                Some(last) if last.layer == *layer => {
+                    last.elements.push(ElementSummary::from(element))
                 }
+                _ => page_layers.push(ElementLayer {
+                    layer: *layer,
+                    elements: vec![ElementSummary::from(element)],
+                }),
+            }
+        }
+        Utils::log_time(start_time, "ModelParser - get_topological_layers");
+
+        Ok(ModelLayeringResult {
+            is_acyclic: true,
+            total_layers: layers.len(),
+            total_result_count: ordered.len(),
+            elements_per_page: page.elements_per_page,
+            total_page: page.total_page,
+            current_page: page.current_page,
+            layers: page_layers,
+            cycle: Vec::new(),
+        })
+    }
+
+    //Get shared ancestors
+    pub async fn get_common_ancestors(
+        &self,
+        model_id: &str,
+        version_number: &str,
+        element_ids: &[String],
+        relationship_types: &[String],
+    ) -> Result<Vec<CommonAncestorResult>, ModelError> {
+        info!(
+            "[ModelParser - get_common_ancestors] model_id: {}, version_number: {}, element_ids: {:?}, relationship_types: {:?}",
+            model_id, version_number, element_ids, relationship_types
+        );
+        let start_time = Instant::now();
+        let snapshot = self.get_snapshot(model_id, version_number).await?;
+        let graph = snapshot.get_graph()?;
+
+        let ancestors = ElementGraphAncestor::common_ancestors(graph, element_ids, |path| {
+            ModelParser::is_relationship_type_selected(relationship_types, path)
+        })?;
+
+        let result = ancestors
+            .into_iter()
+            .filter_map(|a| {
+                snapshot
+                    .get_element(&a.ancestor_id)
+                    .map(|e| CommonAncestorResult {
+                        ancestor: ElementSummary::from(e),
+                        is_lowest: a.is_lowest,
+                        distances: a.distances,
+                    })
+            })
+            .collect();
+        Utils::log_time(start_time, "ModelParser - get_common_ancestors");
+
+        Ok(result)
+    }
+
+    //Get downstream impact
+    pub async fn get_downstream_impact(
+        &self,
+        model_id: &str,
+        version_number: &str,
+        element_id: &str,
+        relationship_types: &[String],
+        depth: usize,
+        page_config: PageConfig,
+    ) -> Result<ModelImpactResult, ModelError> {
+        info!(
+            "[ModelParser - get_downstream_impact] model_id: {}, version_number: {}, element_id: {}, relationship_types: {:?}, depth: {}, page_config: {:?}",
+            model_id, version_number, element_id, relationship_types, depth, page_config
+        );
+        let start_time = Instant::now();
+        let snapshot = self.get_snapshot(model_id, version_number).await?;
+        let graph = snapshot.get_graph()?;
+
+        let impacted = ElementGraphImpact::downstream(
+            graph,
+            element_id,
+            |path| ModelParser::is_relationship_type_selected(relationship_types, path),
+            depth,
+        )?;
+
+        // Group by type, closest first
+        let mut impacted: Vec<(&Element, ImpactedElement)> = impacted
+            .into_iter()
+            .filter_map(|i| snapshot.get_element(&i.element_id).map(|e| (e, i)))
+            .collect();
+        impacted.sort_by(|(a, i), (b, j)| {
+            a.type_
+                .cmp(&b.type_)
+                .then(i.depth.cmp(&j.depth))
+                .then(a.id.cmp(&b.id))
+        });
+        let stats = ModelStats::from_elements(&impacted.iter().map(|(e, _)| *e).collect());
+
+        let (page, limited) = ModelParser::paginate(&impacted, &page_config);
+        let mut groups: Vec<ImpactGroup> = Vec::new();
+        for (element, impacted_element) in limited {
+            let result = ImpactedElementResult {
+                element: ElementSummary::from(element),
+                depth: impacted_element.depth,
+                path: impacted_element
+                    .path
+                    .iter()
+                    .map(|step| ImpactStepResult {
+                        relationship_id: step.relationship_id.clone(),
+                        relationship_type: graph
+                            .get_relationship(&step.relationship_id)
+                            .map(|r| r.type_.to_owned())
+                            .unwrap_or_default(),
+                        source_id: step.source_id.clone(),
+                        target_id: step.target_id.clone(),
+                    })
+                    .collect(),
             };
+            match groups.last_mut() {
+                Some(group) if group.type_ == element.type_ => group.elements.push(result),
+                _ => groups.push(ImpactGroup {
+                    type_: element.type_.clone(),
+                    elements: vec![result],
+                }),
+            }
+        }
+        Utils::log_time(start_time, "ModelParser - get_downstream_impact");
 
-        Ok(model_data)
+        Ok(ModelImpactResult {
+            element_id: element_id.to_owned(),
+            total_result_count: impacted.len(),
+            elements_per_page: page.elements_per_page,
+            total_page: page.total_page,
+            current_page: page.current_page,
+            stats,
+            groups,
+        })
     }
 
-    fn build_relationship_graph(
+    //Get lineage
+    pub async fn get_element_lineage(
         &self,
-        model_id: &String,
-        version_number: u32,
-        model_data: &ModelData,
-    ) -> Option<Arc<ElementGraph>> {
-        println!("[ModelParser - build_relationship_graph] Building relationship graph");
+        model_id: &str,
+        version_number: &str,
+        element_id: &str,
+        ancestor_depth: u32,
+        children_depth: u32,
+    ) -> Result<OutputGraph<String>, ModelError> {
+        info!(
+            "[ModelParser - get_element_lineage] model_id: {}, version_number: {}, element_id: {}, ancestor_depth: {}, children_depth: {}",
+            model_id, version_number, element_id, ancestor_depth, children_depth
+        );
+        let start_time = Instant::now();
+        let snapshot = self.get_snapshot(model_id, version_number).await?;
+        let graph = snapshot.get_graph()?;
+
+        if graph.get_connection(element_id).is_none() {
+            return Err(ModelError::ParsingError(format!("Element: {}", element_id)));
+        }
+        let output = ElementGraphParser::build_output_with_limit(
+            graph,
+            element_id,
+            &snapshot,
+            ancestor_depth.min(MAX_LINEAGE_DEPTH),
+            children_depth.min(MAX_LINEAGE_DEPTH),
+        )?;
+        Utils::log_time(start_time, "ModelParser - get_element_lineage");
+
+        Ok(output)
+    }
+
+    //Query pattern
+    pub async fn query_pattern(
+        &self,
+        model_id: &str,
+        version_number: &str,
+        pattern: &str,
+        page_config: PageConfig,
+    ) -> Result<ModelPatternResult, ModelError> {
+        info!(
+            "[ModelParser - query_pattern] model_id: {}, version_number: {}, pattern: {}, page_config: {:?}",
+            model_id, version_number, pattern, page_config
+        );
+        let start_time = Instant::now();
+        let query = PatternQuery::parse(pattern)?;
+        let snapshot = self.get_snapshot(model_id, version_number).await?;
+        let graph = snapshot.get_graph()?;
+
+        let (rows, is_truncated) =
+            PatternMatcher::new(&query, graph, &snapshot, MAX_PATTERN_ROWS).find();
+        self.reweigh_snapshot(&snapshot);
+        let (page, limited) = ModelParser::paginate(&rows, &page_config);
+        let page_rows = limited
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|(variable, binding)| {
+                        (
+                            variable.clone(),
+                            serde_json::to_value(binding).unwrap_or_default(),
+                        )
+                    })
+                    .collect()
+            })
+            .collect();
+        Utils::log_time(start_time, "ModelParser - query_pattern");
+
+        Ok(ModelPatternResult {
+            variables: query.get_variables(),
+            total_result_count: rows.len(),
+            is_truncated,
+            elements_per_page: page.elements_per_page,
+            total_page: page.total_page,
+            current_page: page.current_page,
+            rows: page_rows,
+        })
+    }
+
+    //Get neighbours
+    pub async fn get_neighbours(
+        &self,
+        model_id: &str,
+        version_number: &str,
+        element_id: &str,
+        direction: TraversalDirection,
+        max_hop: usize,
+        page_config: PageConfig,
+    ) -> Result<ModelNeighbourResult, ModelError> {
+        info!(
+            "[ModelParser - get_neighbours] model_id: {}, version_number: {}, element_id: {}, direction: {:?}, max_hop: {}, page_config: {:?}",
+            model_id, version_number, element_id, direction, max_hop, page_config
+        );
         let start_time = Instant::now();
-        let graph_cache = &self.graph_cache;
-        let elements = &model_data.elements;
-        let relationships = &model_data.relationships;
-
-        //Check if exist in cache
-        let existing_graph = graph_cache.get_ref(&model_id, &version_number.to_string());
-
-        //If not exist, building graph
-        if version_number != 0 && existing_graph.is_none() {
-            println!("[ModelParser - build_relationship_graph] Graph not found cache. Building...");
-            let graph = match ElementConnectorBuilder::build_graph(elements, relationships) {
-                Ok(graph) => {
-                    graph_cache.insert(&model_id, &version_number.to_string(), &graph);
-
-                    //Get reference
-                    let cached_graph = graph_cache
-                        .get_ref(&model_id, &version_number.to_string())
-                        .unwrap();
-                    Some(cached_graph)
+        let snapshot = self.get_snapshot(model_id, version_number).await?;
+        let graph = snapshot.get_graph()?;
+
+        let neighbours = ElementGraphNeighbour::neighbours(
+            graph,
+            element_id,
+            direction,
+            max_hop.min(MAX_NEIGHBOUR_HOP),
+        )?;
+
+        // Keep the ones found in the model before counting pages
+        let neighbours: Vec<_> = neighbours
+            .iter()
+            .filter_map(|n| {
+                let element = snapshot.get_element(&n.element_id)?;
+                let relationship = graph.get_relationship(&n.relationship_id)?;
+                Some((n, element, relationship))
+            })
+            .collect();
+        let (page, limited) = ModelParser::paginate(&neighbours, &page_config);
+
+        let page_neighbours = limited
+            .iter()
+            .map(|(n, element, relationship)| NeighbourResult {
+                element: ElementSummary::from(element),
+                hop: n.hop,
+                direction: n.direction,
+                via_element_id: n.via_element_id.clone(),
+                relationship: RelationshipSummary {
+                    id: relationship.id.to_owned(),
+                    type_: relationship.type_.to_owned(),
+                    nature: relationship.nature.to_owned(),
+                    name: relationship.name.to_owned(),
+                },
+            })
+            .collect();
+        Utils::log_time(start_time, "ModelParser - get_neighbours");
+
+        Ok(ModelNeighbourResult {
+            element_id: element_id.to_owned(),
+            total_result_count: neighbours.len(),
+            elements_per_page: page.elements_per_page,
+            total_page: page.total_page,
+            current_page: page.current_page,
+            neighbours: page_neighbours,
+        })
+    }
+
+    //Validate model
+    pub async fn validate_model(
+        &self,
+        model_id: &str,
+        version_number: &str,
+        page_config: PageConfig,
+    ) -> Result<ModelValidationResult, ModelError> {
+        info!(
+            "[ModelParser - validate_model] model_id: {}, version_number: {}, page_config: {:?}",
+            model_id, version_number, page_config
+        );
+        let start_time = Instant::now();
+        let snapshot = self.get_snapshot(model_id, version_number).await?;
+
+        let findings = ModelValidator::validate(snapshot.get_model_data());
+        let error_count = findings
+            .iter()
+            .filter(|f| f.severity == Severity::Error)
+            .count();
+
+        // Findings are sorted by severity, group the page
+        let (page, limited) = ModelParser::paginate(&findings, &page_config);
+        let mut groups: Vec<ValidationGroup> = Vec::new();
+        for finding in limited {
+            match groups.last_mut() {
+                Some(group) if group.severity == finding.severity => {
+                    group.findings.push(finding.clone())
                 }
-                Err(_) => None,
-            };
-            Utils::log_time(start_time, "Building Relationship graph");
-            graph
-        } else {
-            existing_graph
+                _ => groups.push(ValidationGroup {
+                    severity: finding.severity,
+                    findings: vec![finding.clone()],
+                }),
+            }
         }
+        Utils::log_time(start_time, "ModelParser - validate_model");
+
+        Ok(ModelValidationResult {
+            model_id: snapshot.get_model_id().to_owned(),
+            version: snapshot.get_version(),
+            is_valid: error_count == 0,
+            error_count,
+            warning_count: findings.len() - error_count,
+            total_result_count: findings.len(),
+            elements_per_page: page.elements_per_page,
+            total_page: page.total_page,
+            current_page: page.current_page,
+            groups,
+        })
     }
 
-    fn get_version_number(version_number: &str, model_versions: &Vec<ModelVersionNumber>) -> i32 {
+    //List models
+    pub async fn list_models(
+        &self,
+        model_id_prefix: &str,
+        site_model_id: Option<&str>,
+        page_config: PageConfig,
+    ) -> Result<ModelListResult, ModelError> {
+        info!(
+            "[ModelParser - list_models] model_id_prefix: {}, site_model_id: {:?}, page_config: {:?}",
+            model_id_prefix, site_model_id, page_config
+        );
+        let start_time = Instant::now();
+        // Site model id is only known from the cache, the page is then cut after filtering
+        let range = match site_model_id {
+            Some(_) => ModelRange::default(),
+            None => ModelParser::page_range(&page_config),
+        };
+        let SavedModelPage {
+            total_count,
+            models,
+        } = self
+            .model_source
+            .list_models(model_id_prefix, range)
+            .await?;
+        let models: Vec<ModelListEntry> = models
+            .into_iter()
+            .map(|m| {
+                let site_model_id = self
+                    .snapshot_cache
+                    .peek_ref(&m.model_id, &m.latest_version.to_string())
+                    .map(|s| s.get_model_data().site_model_id.clone());
+                ModelListEntry {
+                    model_id: m.model_id,
+                    version_count: m.version_count,
+                    latest_version: m.latest_version,
+                    site_model_id,
+                }
+            })
+            .filter(|m| site_model_id.is_none_or(|s| m.site_model_id.as_deref() == Some(s)))
+            .collect();
+
+        let (total_result_count, page, models) = match site_model_id {
+            Some(_) => {
+                let (page, limited) = ModelParser::paginate(&models, &page_config);
+                (models.len(), page, limited.to_vec())
+            }
+            None => (
+                total_count,
+                ModelParser::page_of(total_count, &page_config),
+                models,
+            ),
+        };
+        Utils::log_time(start_time, "ModelParser - list_models");
+
+        Ok(ModelListResult {
+            total_result_count,
+            elements_per_page: page.elements_per_page,
+            total_page: page.total_page,
+            current_page: page.current_page,
+            models,
+        })
+    }
+
+    //Query relationship facets
+    #[allow(clippy::too_many_arguments)]
+    pub async fn query_relationship_facets(
+        &self,
+        model_id: &str,
+        version_number: &str,
+        element_id: Option<&str>,
+        types: &str,
+        natures: &str,
+        facet_type: &str,
+        query: &str,
+        is_detail: bool,
+        depth: usize,
+        page_config: PageConfig,
+    ) -> Result<ModelRelationshipFacetResult, ModelError> {
+        info!(
+            "[ModelParser - query_relationship_facets] model_id: {}, version_number: {}, element_id: {:?}, types: {}, natures: {}, facet_type: {}, query: {}, is_detail: {}, depth: {}, page_config: {:?}",
+            model_id, version_number, element_id, types, natures, facet_type, query, is_detail, depth, page_config
+        );
+        let start_time = Instant::now();
+        let facet_type = FacetType::parse(facet_type).ok_or(ModelError::InvalidInput(format!(
+            "facet type {}, expected coreFacets, dynamicFacets or facets",
+            facet_type
+        )))?;
+        let snapshot = self.get_snapshot(model_id, version_number).await?;
+
+        // Relationships touching the element when given
+        let relationships = match element_id {
+            Some(id) => snapshot.get_element_relationships(id),
+            None => snapshot.get_model_data().relationships.iter().collect(),
+        };
+        let relationships: Vec<&Relationship> = relationships
+            .into_iter()
+            .filter(|r| types == ALL || r.type_ == types)
+            .filter(|r| natures == ALL || r.nature == natures)
+            .collect();
+        info!(
+            "[ModelParser - query_relationship_facets] {} relationships after filtered",
+            relationships.len()
+        );
+
+        let values = ModelData::get_json_values(relationships, Some(facet_type), query, is_detail);
+        let (page, limited) = ModelParser::paginate(&values, &page_config);
+        let relationships = match depth > 0 {
+            true => cubs_model::truncate_value(limited, depth),
+            false => limited.to_vec(),
+        };
+        Utils::log_time(start_time, "ModelParser - query_relationship_facets");
+
+        Ok(ModelRelationshipFacetResult {
+            model_id: snapshot.get_model_id().to_owned(),
+            version: snapshot.get_version(),
+            total_result_count: values.len(),
+            elements_per_page: page.elements_per_page,
+            total_page: page.total_page,
+            current_page: page.current_page,
+            relationships,
+        })
+    }
+
+    // Load a version into the snapshot cache, "" or latest for the latest version
+    pub async fn preload_model(
+        &self,
+        model_id: &str,
+        version_number: &str,
+    ) -> Result<Arc<ModelSnapshot>, ModelError> {
+        self.get_snapshot(model_id, version_number).await
+    }
+
+    // Version number as is, "" or latest for the latest version of the source
+    pub async fn resolve_version(&self, model_id: &str, version_number: &str) -> i32 {
+        match version_number.parse::<i32>() {
+            Ok(version_number) => version_number,
+            Err(_) => {
+                let model_version =
+                    parser::read_model_data_versions(self.model_source, &model_id.to_owned())
+                        .await
+                        .unwrap_or_default();
+                ModelParser::get_version_number(version_number, &model_version)
+            }
+        }
+    }
+
+    // Resolve the version then get its snapshot
+    async fn get_snapshot(
+        &self,
+        model_id: &str,
+        version_number: &str,
+    ) -> Result<Arc<ModelSnapshot>, ModelError> {
+        let model_id = model_id.to_owned();
+        let i_version_number = self.resolve_version(&model_id, version_number).await;
+        self.get_snapshot_with_version(&model_id, i_version_number)
+            .await
+    }
+
+    // Get from cache else build from the model source, one load per version at a time
+    async fn get_snapshot_with_version(
+        &self,
+        model_id: &String,
+        version_number: i32,
+    ) -> Result<Arc<ModelSnapshot>, ModelError> {
+        let version = version_number.to_string();
+        self.snapshot_cache
+            .get_or_load(model_id, &version, || self.load_snapshot(model_id, version_number))
+            .await
+    }
+
+    async fn load_snapshot(
+        &self,
+        model_id: &String,
+        version_number: i32,
+    ) -> Result<ModelSnapshot, ModelError> {
+        // Fetch, decode then build, each stage log its own time
+        let start_time = Instant::now();
+        let model_data =
+            parser::read_model_data_with_version(self.model_source, model_id, version_number)
+                .await
+                .map_err(|e| {
+                    info!(
+                        "[ModelParser - get_snapshot] model id {} not found or having issue retrieve model: {}",
+                        model_id, e
+                    );
+                    e
+                })?;
+
+        // Building graph and index is CPU bound
+        let snapshot_mode = self.snapshot_mode;
+        let snapshot = tokio::task::spawn_blocking(move || {
+            ModelSnapshot::build_with_mode(model_data, snapshot_mode)
+        })
+            .await
+            .map_err(|e| ModelError::ModelGraphBuildingError(e.to_string()))?;
+        Utils::log_time(start_time, "ModelParser - get_snapshot - load");
+
+        Ok(snapshot)
+    }
+
+    // Lazy facets parsed by a query grow the snapshot after it was weighed by the cache
+    fn reweigh_snapshot(&self, snapshot: &ModelSnapshot) {
+        if snapshot.get_mode() == SnapshotMode::Lazy {
+            self.snapshot_cache
+                .reweigh(snapshot.get_model_id(), &snapshot.get_version().to_string());
+        }
+    }
+
+    fn is_relationship_type_selected(relationship_types: &[String], path: &Path) -> bool {
+        let type_ = path.get_relationship().type_;
+        relationship_types.is_empty() || relationship_types.iter().any(|t| t == type_)
+    }
+
+    fn paginate<'b, T>(items: &'b [T], page_config: &PageConfig) -> (Page, &'b [T]) {
+        let page = ModelParser::page_of(items.len(), page_config);
+        let elements_per_page = page.elements_per_page;
+        let start = page_config
+            .page_to_get
+            .saturating_sub(1)
+            .checked_mul(elements_per_page);
+
+        // Past the last page, or too far to be one
+        match start {
+            Some(start) if start < items.len() => {
+                let end = start.saturating_add(elements_per_page).min(items.len());
+                (page, &items[start..end])
+            }
+            _ => (page, &[]),
+        }
+    }
+
+    fn page_of(total_count: usize, page_config: &PageConfig) -> Page {
+        let elements_per_page = page_config.elements_per_page.max(1);
+        Page {
+            elements_per_page,
+            total_page: total_count.div_ceil(elements_per_page),
+            current_page: page_config.page_to_get,
+        }
+    }
+
+    // Range read by a source for the page, empty when too far to be one
+    fn page_range(page_config: &PageConfig) -> ModelRange {
+        let elements_per_page = page_config.elements_per_page.max(1);
+        let offset = page_config
+            .page_to_get
+            .saturating_sub(1)
+            .checked_mul(elements_per_page);
+        ModelRange {
+            offset: offset.unwrap_or(usize::MAX),
+            limit: Some(offset.map_or(0, |_| elements_per_page)),
+        }
+    }
+
+    fn get_version_number(version_number: &str, model_versions: &[ModelVersionNumber]) -> i32 {
         version_number
             .parse::<i32>()
             .unwrap_or_else(|_| model_versions.first().map_or(0, |v| v.vers_no))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::model_source::ModelFileSource;
+
+    #[test]
+    fn test_paginate() {
+        let items: Vec<usize> = (0..5).collect();
+        let page_config = |page_to_get, elements_per_page| PageConfig {
+            elements_per_page,
+            page_to_get,
+        };
+
+        let (page, limited) = ModelParser::paginate(&items, &page_config(2, 2));
+        assert_eq!(page.total_page, 3);
+        assert_eq!(limited, &[2, 3]);
+        assert_eq!(ModelParser::paginate(&items, &page_config(3, 2)).1, &[4]);
+        assert!(ModelParser::paginate(&items, &page_config(4, 2)).1.is_empty());
+
+        // No overflow on huge pages
+        assert!(ModelParser::paginate(&items, &page_config(usize::MAX, 2)).1.is_empty());
+        assert_eq!(ModelParser::paginate(&items, &page_config(1, usize::MAX)).1.len(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_version() {
+        let path =
+            std::env::temp_dir().join(format!("resolve_version_test_{}.json", std::process::id()));
+        let json = r#"{"schemaVersion": "1", "modelId": "m1", "siteModelId": "s1", "version": 2,
+            "elements": [], "relationships": []}"#;
+        std::fs::write(&path, json).unwrap();
+        let (source, _) = ModelFileSource::open(path.clone()).await.unwrap();
+        let model_parser = ModelParser::new(QuickCache::new(1), QuickCache::new(1), &source);
+
+        assert_eq!(model_parser.resolve_version("m1", "7").await, 7);
+        assert_eq!(model_parser.resolve_version("m1", "latest").await, 2);
+        assert_eq!(model_parser.resolve_version("m1", "").await, 2);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_query_model_empty_page() {
+        let path =
+            std::env::temp_dir().join(format!("query_model_test_{}.json", std::process::id()));
+        let json = r#"{"schemaVersion": "1", "modelId": "m1", "siteModelId": "s1", "version": 2,
+            "elements": [{"id": "p1", "type": "Pump", "nature": "Asset", "name": "P1", "version": 1, "facets": {}}],
+            "relationships": []}"#;
+        std::fs::write(&path, json).unwrap();
+        let (source, _) = ModelFileSource::open(path.clone()).await.unwrap();
+        let model_parser = ModelParser::new(QuickCache::new(1), QuickCache::new(1), &source);
+        let query = |types: &str, page_to_get| {
+            model_parser.query_model(
+                "m1".to_string(),
+                "2".to_string(),
+                String::new(),
+                false,
+                types.to_string(),
+                ALL.to_string(),
+                String::new(),
+                0,
+                PageConfig {
+                    elements_per_page: 10,
+                    page_to_get,
+                },
+                String::new(),
+                false,
+            )
+        };
+
+        // No element of the type
+        let result = query("Nope", 1).await.unwrap();
+        assert_eq!(result.total_result_count, 0);
+        assert_eq!(result.page_count.total_page, 0);
+        assert_eq!(result.data, "[]");
+
+        // Past the last page
+        let result = query(ALL, 3).await.unwrap();
+        assert_eq!(result.total_result_count, 1);
+        assert_eq!(result.page_count.current_page, 3);
+        assert_eq!(result.data, "[]");
+        assert_eq!(query(ALL, 1).await.unwrap().page_count.total_page, 1);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_page_range() {
+        let page_config = |page_to_get, elements_per_page| PageConfig {
+            elements_per_page,
+            page_to_get,
+        };
+        assert_eq!(
+            ModelParser::page_range(&page_config(3, 2)),
+            ModelRange {
+                offset: 4,
+                limit: Some(2)
+            }
+        );
+        assert_eq!(ModelParser::page_range(&page_config(0, 0)).offset, 0);
+        assert_eq!(
+            ModelParser::page_range(&page_config(usize::MAX, 2)).limit,
+            Some(0)
+        );
+        assert_eq!(ModelParser::page_of(5, &page_config(1, 2)).total_page, 3);
+    }
+}
@@ -0,0 +1,421 @@
+use std::collections::HashMap;
+use std::env;
+use std::mem::size_of;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+use serde::Serialize;
+
+use crate::info;
+use crate::model::{
+    cubs_model::{Element, ModelData, Relationship},
+    element_graph::ElementGraph,
+    element_parser::ElementConnectorBuilder,
+    model_dict::{ModelDictionary, ModelStats},
+    model_error::ModelError,
+    size_estimate::{EstimateSize, hash_map_size},
+    utils::Utils,
+};
+
+// Everything derived from one model version, built once and shared by every tool
+
+// When element facets are parsed, set with MODEL_SNAPSHOT_MODE
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SnapshotMode {
+    // Every facet map parsed when the snapshot is built
+    #[default]
+    Eager,
+    // Facet maps kept as raw json until a pointer, projection or output reads them
+    Lazy,
+}
+
+impl SnapshotMode {
+    pub fn from_env() -> Result<Self, ModelError> {
+        match env::var("MODEL_SNAPSHOT_MODE").as_deref() {
+            Err(_) | Ok("") | Ok("eager") => Ok(SnapshotMode::Eager),
+            Ok("lazy") => Ok(SnapshotMode::Lazy),
+            Ok(other) => Err(ModelError::InvalidInput(format!(
+                "MODEL_SNAPSHOT_MODE {}, expected eager or lazy",
+                other
+            ))),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ModelSnapshot {
+    data: ModelData,
+    mode: SnapshotMode,
+    // None when the relationships do not form a valid graph
+    graph: Option<ElementGraph>,
+    // Element id : position in data.elements
+    element_index: HashMap<String, usize>,
+    // Type / nature : element positions in model order
+    type_index: HashMap<String, Vec<usize>>,
+    nature_index: HashMap<String, Vec<usize>>,
+    // Relationship id : position in data.relationships
+    relationship_index: HashMap<String, usize>,
+    // Element id : positions of relationships from or to it, in model order
+    element_relationship_index: HashMap<String, Vec<usize>>,
+    stats: ModelStats,
+    // Heap estimate at build time
+    estimated_size: usize,
+    // Lazy facets parsed since the build
+    lazy_parsed_size: Arc<AtomicUsize>,
+}
+
+impl ModelSnapshot {
+    pub fn build(data: ModelData) -> Self {
+        Self::build_with_mode(data, SnapshotMode::Eager)
+    }
+
+    pub fn build_with_mode(mut data: ModelData, mode: SnapshotMode) -> Self {
+        let start_time = Instant::now();
+        let lazy_parsed_size = Arc::new(AtomicUsize::new(0));
+        match mode {
+            SnapshotMode::Eager => data.parse_facets(),
+            SnapshotMode::Lazy => data.track_parsed_size(&lazy_parsed_size),
+        }
+
+        let graph = match ElementConnectorBuilder::build_graph(&data.elements, &data.relationships)
+        {
+            Ok(graph) => Some(graph),
+            Err(e) => {
+                info!(
+                    "[ModelSnapshot - build] model id {} version {} without graph: {}",
+                    data.model_id, data.version, e
+                );
+                None
+            }
+        };
+
+        // Keep the first element when id is duplicated
+        let mut element_index: HashMap<String, usize> = HashMap::with_capacity(data.elements.len());
+        let mut type_index: HashMap<String, Vec<usize>> = HashMap::new();
+        let mut nature_index: HashMap<String, Vec<usize>> = HashMap::new();
+        for (i, element) in data.elements.iter().enumerate() {
+            element_index.entry(element.id.clone()).or_insert(i);
+            type_index.entry(element.type_.clone()).or_default().push(i);
+            nature_index
+                .entry(element.nature.clone())
+                .or_default()
+                .push(i);
+        }
+
+        let mut relationship_index: HashMap<String, usize> =
+            HashMap::with_capacity(data.relationships.len());
+        let mut element_relationship_index: HashMap<String, Vec<usize>> = HashMap::new();
+        for (i, relationship) in data.relationships.iter().enumerate() {
+            relationship_index
+                .entry(relationship.id.clone())
+                .or_insert(i);
+            element_relationship_index
+                .entry(relationship.source_id.clone())
+                .or_default()
+                .push(i);
+            // Self loop listed once
+            if relationship.target_id != relationship.source_id {
+                element_relationship_index
+                    .entry(relationship.target_id.clone())
+                    .or_default()
+                    .push(i);
+            }
+        }
+
+        let stats = ModelDictionary::from(&data, Vec::new()).model_stats;
+        let index_size = |index_len: usize, keys: usize| {
+            hash_map_size(index_len, size_of::<(String, usize)>()) + keys
+        };
+        let estimated_size = data.estimate_size()
+            + graph.as_ref().map_or(0, EstimateSize::estimate_size)
+            + index_size(
+                element_index.len(),
+                element_index.keys().map(String::len).sum(),
+            )
+            + index_size(
+                relationship_index.len(),
+                relationship_index.keys().map(String::len).sum(),
+            )
+            + hash_map_size(
+                element_relationship_index.len(),
+                size_of::<(String, Vec<usize>)>(),
+            )
+            + element_relationship_index
+                .keys()
+                .map(String::len)
+                .sum::<usize>()
+            + size_of::<usize>() * 2 * data.elements.len()
+            + size_of::<usize>() * 2 * data.relationships.len();
+        Utils::log_time(start_time, "ModelSnapshot - build");
+
+        Self {
+            data,
+            mode,
+            graph,
+            element_index,
+            type_index,
+            nature_index,
+            relationship_index,
+            element_relationship_index,
+            stats,
+            estimated_size,
+            lazy_parsed_size,
+        }
+    }
+
+    pub fn get_model_id(&self) -> &str {
+        &self.data.model_id
+    }
+
+    pub fn get_version(&self) -> u32 {
+        self.data.version
+    }
+
+    pub fn get_mode(&self) -> SnapshotMode {
+        self.mode
+    }
+
+    pub fn get_model_data(&self) -> &ModelData {
+        &self.data
+    }
+
+    pub fn get_graph(&self) -> Result<&ElementGraph, ModelError> {
+        self.graph
+            .as_ref()
+            .ok_or(ModelError::ModelGraphBuildingError(format!(
+                "Unable to build graph for model {} version {}",
+                self.data.model_id, self.data.version
+            )))
+    }
+
+    pub fn get_element(&self, id: &str) -> Option<&Element> {
+        self.element_index.get(id).map(|i| &self.data.elements[*i])
+    }
+
+    pub fn get_relationship(&self, id: &str) -> Option<&Relationship> {
+        self.relationship_index
+            .get(id)
+            .map(|i| &self.data.relationships[*i])
+    }
+
+    // Relationships from or to the element in model order
+    pub fn get_element_relationships(&self, element_id: &str) -> Vec<&Relationship> {
+        self.element_relationship_index
+            .get(element_id)
+            .map(|positions| {
+                positions
+                    .iter()
+                    .map(|i| &self.data.relationships[*i])
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    // Elements in model order, None does not filter
+    pub fn get_elements_with_type_nature(
+        &self,
+        type_: Option<&str>,
+        nature: Option<&str>,
+    ) -> Vec<&Element> {
+        let positions = match (type_, nature) {
+            (None, None) => return self.data.elements.iter().collect(),
+            (Some(type_), None) => self.type_index.get(type_),
+            (None, Some(nature)) => self.nature_index.get(nature),
+            // Start from the smaller index
+            (Some(type_), Some(nature)) => {
+                let by_type = self.type_index.get(type_);
+                let by_nature = self.nature_index.get(nature);
+                match (by_type, by_nature) {
+                    (Some(t), Some(n)) if t.len() <= n.len() => Some(t),
+                    (Some(_), Some(n)) => Some(n),
+                    _ => None,
+                }
+            }
+        };
+
+        positions
+            .map(|positions| {
+                positions
+                    .iter()
+                    .map(|i| &self.data.elements[*i])
+                    .filter(|e| type_.is_none_or(|t| e.type_ == t))
+                    .filter(|e| nature.is_none_or(|n| e.nature == n))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    // Elements in model order, unknown ids are ignored
+    pub fn get_elements_with_ids(&self, ids: &[String]) -> Vec<&Element> {
+        let mut positions: Vec<usize> = ids
+            .iter()
+            .filter_map(|id| self.element_index.get(id).copied())
+            .collect();
+        positions.sort_unstable();
+        positions.dedup();
+        positions
+            .into_iter()
+            .map(|i| &self.data.elements[i])
+            .collect()
+    }
+
+    pub fn get_stats(&self) -> &ModelStats {
+        &self.stats
+    }
+}
+
+impl EstimateSize for ModelSnapshot {
+    fn estimate_size(&self) -> usize {
+        self.estimated_size + self.lazy_parsed_size.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::cubs_model::{CusObject, FacetType};
+    use crate::model::size_estimate::map_size;
+    use crate::model::test_fixture;
+    use serde_json::json;
+
+    fn build_model(relationships: serde_json::Value) -> ModelData {
+        test_fixture::build_model(
+            3,
+            json!([
+                {"id": "p1", "type": "Pump", "nature": "Asset", "name": "P1", "version": 1, "facets": {}},
+                {"id": "t1", "type": "Tank", "nature": "Asset", "name": "T1", "version": 1, "facets": {}}
+            ]),
+            relationships,
+        )
+    }
+
+    #[test]
+    fn test_build() {
+        let snapshot = ModelSnapshot::build(build_model(json!([
+            {"id": "r1", "sourceId": "p1", "targetId": "t1", "type": "feeds", "nature": "Flow", "version": 1, "facets": {}}
+        ])));
+
+        assert_eq!(snapshot.get_model_id(), "m1");
+        assert_eq!(snapshot.get_version(), 3);
+        assert_eq!(snapshot.get_element("t1").unwrap().name, "T1");
+        assert!(snapshot.get_element("unknown").is_none());
+        assert_eq!(
+            snapshot
+                .get_stats()
+                .elements_stats
+                .as_ref()
+                .unwrap()
+                .all_count,
+            2
+        );
+
+        assert_eq!(snapshot.get_relationship("r1").unwrap().source_id, "p1");
+        assert!(snapshot.get_relationship("p1").is_none());
+        assert_eq!(snapshot.get_element_relationships("t1")[0].id, "r1");
+        assert!(snapshot.get_element_relationships("x1").is_empty());
+
+        let graph = snapshot.get_graph().unwrap();
+        assert_eq!(graph.get_connection_count(), 2);
+        assert_eq!(graph.get_relationship("r1").unwrap().type_, "feeds");
+    }
+
+    #[test]
+    fn test_element_relationships() {
+        let snapshot = ModelSnapshot::build(build_model(json!([
+            {"id": "r1", "sourceId": "p1", "targetId": "t1", "type": "feeds", "nature": "Flow", "version": 1, "facets": {}},
+            {"id": "r2", "sourceId": "t1", "targetId": "t1", "type": "loop", "nature": "Flow", "version": 1, "facets": {}},
+            {"id": "r3", "sourceId": "t1", "targetId": "p1", "type": "feeds", "nature": "Flow", "version": 1, "facets": {}}
+        ])));
+
+        let ids = |relationships: Vec<&Relationship>| -> Vec<String> {
+            relationships.iter().map(|r| r.id.clone()).collect()
+        };
+        assert_eq!(
+            ids(snapshot.get_element_relationships("p1")),
+            vec!["r1", "r3"]
+        );
+        assert_eq!(
+            ids(snapshot.get_element_relationships("t1")),
+            vec!["r1", "r2", "r3"]
+        );
+    }
+
+    #[test]
+    fn test_build_without_graph() {
+        // Dangling relationship, data is still usable
+        let snapshot = ModelSnapshot::build(build_model(json!([
+            {"id": "r1", "sourceId": "p1", "targetId": "x1", "type": "feeds", "nature": "Flow", "version": 1, "facets": {}}
+        ])));
+
+        assert!(snapshot.get_graph().is_err());
+        assert_eq!(snapshot.get_element("p1").unwrap().type_, "Pump");
+    }
+
+    #[test]
+    fn test_lookup_by_type_nature_and_ids() {
+        let snapshot = ModelSnapshot::build(build_model(json!([])));
+
+        let ids = |elements: Vec<&Element>| -> Vec<String> {
+            elements.iter().map(|e| e.id.clone()).collect()
+        };
+        assert_eq!(
+            ids(snapshot.get_elements_with_type_nature(None, None)),
+            vec!["p1", "t1"]
+        );
+        assert_eq!(
+            ids(snapshot.get_elements_with_type_nature(Some("Tank"), None)),
+            vec!["t1"]
+        );
+        assert_eq!(
+            ids(snapshot.get_elements_with_type_nature(None, Some("Asset"))),
+            vec!["p1", "t1"]
+        );
+        assert_eq!(
+            ids(snapshot.get_elements_with_type_nature(Some("Pump"), Some("Asset"))),
+            vec!["p1"]
+        );
+        assert!(
+            snapshot
+                .get_elements_with_type_nature(Some("Pump"), Some("Location"))
+                .is_empty()
+        );
+
+        let requested = vec!["t1".to_string(), "x1".to_string(), "p1".to_string()];
+        assert_eq!(
+            ids(snapshot.get_elements_with_ids(&requested)),
+            vec!["p1", "t1"]
+        );
+    }
+
+    #[test]
+    fn test_build_with_mode() {
+        let model = || {
+            let mut model = build_model(json!([]));
+            model.elements[0].facets =
+                serde_json::from_value(json!({"flowRate": {"value": 12.5}})).unwrap();
+            model
+        };
+
+        let snapshot = ModelSnapshot::build(model());
+        assert_eq!(snapshot.get_mode(), SnapshotMode::Eager);
+        assert!(snapshot.get_element("p1").unwrap().facets.is_parsed());
+
+        let snapshot = ModelSnapshot::build_with_mode(model(), SnapshotMode::Lazy);
+        let built_size = snapshot.estimate_size();
+        let element = snapshot.get_element("p1").unwrap();
+        assert!(!element.facets.is_parsed());
+        assert_eq!(
+            element.get_json_value(&FacetType::Facets, "/flowRate/value", false),
+            Some(json!(12.5))
+        );
+        assert!(element.facets.is_parsed());
+
+        // Parsed map is added once to the estimate
+        let parsed_size = snapshot.estimate_size() - built_size;
+        assert_eq!(parsed_size, map_size(element.facets.get()));
+        element.get_json_value(&FacetType::Facets, "/flowRate/value", false);
+        assert_eq!(snapshot.estimate_size(), built_size + parsed_size);
+    }
+}
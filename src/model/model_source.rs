@@ -0,0 +1,1001 @@
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+
+use serde::Serialize;
+use sqlx::Row;
+
+use crate::info;
+use crate::model::{
+    cubs_model::{ModelData, ModelVersionNumber},
+    model_error::ModelError,
+    parser,
+};
+
+// Where saved models are read from. Model bytes are JSON, optionally gzipped.
+//
+// Filesystem / HTTP layout:
+//   <root>/<model_id>/<version>.json[.gz]
+//   <root>/<model_id>/index.json  (HTTP only, list of version numbers)
+//   <root>/index.json  (HTTP only, list of model ids)
+
+pub type SourceFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, ModelError>> + Send + 'a>>;
+
+pub trait ModelSource: Send + Sync + std::fmt::Debug {
+    // Versions from the latest, only the version number is needed
+    fn list_versions<'a>(&'a self, model_id: &'a str) -> SourceFuture<'a, Vec<ModelVersionNumber>>;
+
+    // Versions from the latest with payload size and metadata when the source records them
+    fn list_version_details<'a>(
+        &'a self,
+        model_id: &'a str,
+    ) -> SourceFuture<'a, Vec<ModelVersionNumber>> {
+        self.list_versions(model_id)
+    }
+
+    fn fetch_model<'a>(&'a self, model_id: &'a str, version: i32) -> SourceFuture<'a, Vec<u8>>;
+
+    // Model ids starting with the prefix ordered by model id, only the range is read
+    fn list_models<'a>(
+        &'a self,
+        model_id_prefix: &'a str,
+        range: ModelRange,
+    ) -> SourceFuture<'a, SavedModelPage>;
+
+    fn get_name(&self) -> &'static str;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SavedModelSummary {
+    pub model_id: String,
+    pub version_count: usize,
+    pub latest_version: i32,
+}
+
+impl SavedModelSummary {
+    fn from_versions(model_id: String, versions: &[i32]) -> Option<Self> {
+        Some(Self {
+            model_id,
+            version_count: versions.len(),
+            latest_version: *versions.iter().max()?,
+        })
+    }
+}
+
+// Models to list after skipping offset, every remaining one without limit
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ModelRange {
+    pub offset: usize,
+    pub limit: Option<usize>,
+}
+
+impl ModelRange {
+    fn apply<T>(&self, items: Vec<T>) -> Vec<T> {
+        let items = items.into_iter().skip(self.offset);
+        match self.limit {
+            Some(limit) => items.take(limit).collect(),
+            None => items.collect(),
+        }
+    }
+}
+
+// Models in the range, total_count counts every model matching the prefix
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SavedModelPage {
+    pub total_count: usize,
+    pub models: Vec<SavedModelSummary>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ModelSourceConfig {
+    Postgres(PostgresTableConfig),
+    Filesystem(PathBuf),
+    Http(String),
+}
+
+impl ModelSourceConfig {
+    // MODEL_SOURCE=postgres|filesystem|http with MODEL_SOURCE_PATH / MODEL_SOURCE_URL
+    pub fn from_env() -> Result<Self, ModelError> {
+        let source = std::env::var("MODEL_SOURCE").unwrap_or_else(|_| "postgres".to_string());
+        match source.to_lowercase().as_str() {
+            "postgres" => PostgresTableConfig::from_env().map(ModelSourceConfig::Postgres),
+            "filesystem" => std::env::var("MODEL_SOURCE_PATH")
+                .map(|path| ModelSourceConfig::Filesystem(PathBuf::from(path)))
+                .map_err(|_| {
+                    ModelError::InvalidInput(
+                        "MODEL_SOURCE_PATH must be set for filesystem source".to_string(),
+                    )
+                }),
+            "http" => std::env::var("MODEL_SOURCE_URL")
+                .map(|url| ModelSourceConfig::Http(url.trim_end_matches('/').to_string()))
+                .map_err(|_| {
+                    ModelError::InvalidInput(
+                        "MODEL_SOURCE_URL must be set for http source".to_string(),
+                    )
+                }),
+            other => Err(ModelError::InvalidInput(format!(
+                "Unknown MODEL_SOURCE {}, expected postgres, filesystem or http",
+                other
+            ))),
+        }
+    }
+}
+
+// How the saved model payload column is stored
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PayloadEncoding {
+    // bytea holding gzipped JSON
+    Gzip,
+    // text or json column
+    Json,
+    Jsonb,
+}
+
+impl PayloadEncoding {
+    pub fn parse(encoding: &str) -> Result<Self, ModelError> {
+        match encoding.to_lowercase().as_str() {
+            "gzip" => Ok(PayloadEncoding::Gzip),
+            "json" => Ok(PayloadEncoding::Json),
+            "jsonb" => Ok(PayloadEncoding::Jsonb),
+            other => Err(ModelError::InvalidInput(format!(
+                "Unknown payload encoding {}, expected gzip, json or jsonb",
+                other
+            ))),
+        }
+    }
+}
+
+// Table holding one row per model version
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PostgresTableConfig {
+    pub schema: String,
+    pub table: String,
+    pub model_id_column: String,
+    pub version_column: String,
+    pub payload_column: String,
+    pub payload_encoding: PayloadEncoding,
+    // Optional metadata columns, reported with the versions when set
+    pub created_at_column: Option<String>,
+    pub updated_at_column: Option<String>,
+    pub schema_version_column: Option<String>,
+}
+
+impl Default for PostgresTableConfig {
+    fn default() -> Self {
+        Self {
+            schema: "cubs_object_model".to_string(),
+            table: "saved_model".to_string(),
+            model_id_column: "model_id".to_string(),
+            version_column: "vers_no".to_string(),
+            payload_column: "saved_gzip".to_string(),
+            payload_encoding: PayloadEncoding::Gzip,
+            created_at_column: None,
+            updated_at_column: None,
+            schema_version_column: None,
+        }
+    }
+}
+
+impl PostgresTableConfig {
+    // MODEL_DB_SCHEMA, MODEL_DB_TABLE, MODEL_DB_ID_COLUMN, MODEL_DB_VERSION_COLUMN,
+    // MODEL_DB_PAYLOAD_COLUMN and MODEL_DB_PAYLOAD_ENCODING, each defaulting to the saved_model table.
+    // MODEL_DB_CREATED_AT_COLUMN, MODEL_DB_UPDATED_AT_COLUMN and MODEL_DB_SCHEMA_VERSION_COLUMN are optional
+    pub fn from_env() -> Result<Self, ModelError> {
+        let default = Self::default();
+        let var = |name: &str, default: String| std::env::var(name).unwrap_or(default);
+        let payload_encoding = match std::env::var("MODEL_DB_PAYLOAD_ENCODING") {
+            Ok(encoding) => PayloadEncoding::parse(&encoding)?,
+            Err(_) => default.payload_encoding,
+        };
+        let config = Self {
+            schema: var("MODEL_DB_SCHEMA", default.schema),
+            table: var("MODEL_DB_TABLE", default.table),
+            model_id_column: var("MODEL_DB_ID_COLUMN", default.model_id_column),
+            version_column: var("MODEL_DB_VERSION_COLUMN", default.version_column),
+            payload_column: var("MODEL_DB_PAYLOAD_COLUMN", default.payload_column),
+            payload_encoding,
+            created_at_column: std::env::var("MODEL_DB_CREATED_AT_COLUMN").ok(),
+            updated_at_column: std::env::var("MODEL_DB_UPDATED_AT_COLUMN").ok(),
+            schema_version_column: std::env::var("MODEL_DB_SCHEMA_VERSION_COLUMN").ok(),
+        };
+        config.validate()?;
+        Ok(config)
+    }
+
+    // Names end up in the SQL text, only plain identifiers are accepted
+    fn validate(&self) -> Result<(), ModelError> {
+        let names = [
+            &self.schema,
+            &self.table,
+            &self.model_id_column,
+            &self.version_column,
+            &self.payload_column,
+        ];
+        let optional_names = [
+            &self.created_at_column,
+            &self.updated_at_column,
+            &self.schema_version_column,
+        ];
+        for name in names
+            .into_iter()
+            .chain(optional_names.into_iter().flatten())
+        {
+            let is_identifier = name
+                .chars()
+                .next()
+                .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+                && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+            if !is_identifier {
+                return Err(ModelError::InvalidInput(format!(
+                    "Invalid database identifier {}",
+                    name
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn versions_query(&self) -> String {
+        format!(
+            r#"SELECT "{version}"::int4 FROM "{schema}"."{table}" WHERE "{model_id}" = $1 ORDER BY "{version}" DESC"#,
+            version = self.version_column,
+            schema = self.schema,
+            table = self.table,
+            model_id = self.model_id_column,
+        )
+    }
+
+    // Stored payload size is read from its header, only the jsonb schemaVersion reads the payload
+    fn version_details_query(&self) -> String {
+        let text_column = |column: &Option<String>| match column {
+            Some(column) => format!(r#""{}"::text"#, column),
+            None => "NULL::text".to_string(),
+        };
+        // Without a column, jsonb payload still exposes its schemaVersion cheaply
+        let schema_version = match (&self.schema_version_column, self.payload_encoding) {
+            (None, PayloadEncoding::Jsonb) => {
+                format!(r#""{}"->>'schemaVersion'"#, self.payload_column)
+            }
+            (column, _) => text_column(column),
+        };
+        format!(
+            r#"SELECT "{version}"::int4, pg_column_size("{payload}")::int8, {created_at}, {updated_at}, {schema_version} FROM "{schema}"."{table}" WHERE "{model_id}" = $1 ORDER BY "{version}" DESC"#,
+            version = self.version_column,
+            payload = self.payload_column,
+            created_at = text_column(&self.created_at_column),
+            updated_at = text_column(&self.updated_at_column),
+            schema_version = schema_version,
+            schema = self.schema,
+            table = self.table,
+            model_id = self.model_id_column,
+        )
+    }
+
+    // $1 is the escaped prefix, NULL limit returns every model
+    fn models_query(&self) -> String {
+        format!(
+            r#"SELECT "{model_id}"::text, count(*)::int8, max("{version}")::int4 FROM "{schema}"."{table}" WHERE "{model_id}"::text LIKE $1 || '%' GROUP BY "{model_id}" ORDER BY "{model_id}" LIMIT $2 OFFSET $3"#,
+            model_id = self.model_id_column,
+            version = self.version_column,
+            schema = self.schema,
+            table = self.table,
+        )
+    }
+
+    fn model_count_query(&self) -> String {
+        format!(
+            r#"SELECT count(DISTINCT "{model_id}")::int8 FROM "{schema}"."{table}" WHERE "{model_id}"::text LIKE $1 || '%'"#,
+            model_id = self.model_id_column,
+            schema = self.schema,
+            table = self.table,
+        )
+    }
+
+    fn payload_query(&self) -> String {
+        // Json columns are read back as text
+        let payload = match self.payload_encoding {
+            PayloadEncoding::Gzip => format!(r#""{}""#, self.payload_column),
+            PayloadEncoding::Json | PayloadEncoding::Jsonb => {
+                format!(r#""{}"::text"#, self.payload_column)
+            }
+        };
+        format!(
+            r#"SELECT {payload} FROM "{schema}"."{table}" WHERE "{model_id}" = $1 and "{version}" = $2"#,
+            payload = payload,
+            schema = self.schema,
+            table = self.table,
+            model_id = self.model_id_column,
+            version = self.version_column,
+        )
+    }
+}
+
+// Version, payload size, created at, updated at, schema version
+type VersionRow = (
+    i32,
+    Option<i64>,
+    Option<String>,
+    Option<String>,
+    Option<String>,
+);
+
+// Postgres
+#[derive(Debug)]
+pub struct PostgresModelSource {
+    pg_pool: sqlx::Pool<sqlx::Postgres>,
+    payload_encoding: PayloadEncoding,
+    // Built once from the table config
+    versions_query: String,
+    version_details_query: String,
+    models_query: String,
+    model_count_query: String,
+    payload_query: String,
+}
+
+impl PostgresModelSource {
+    pub fn new(pg_pool: sqlx::Pool<sqlx::Postgres>, config: &PostgresTableConfig) -> Self {
+        Self {
+            pg_pool,
+            payload_encoding: config.payload_encoding,
+            versions_query: config.versions_query(),
+            version_details_query: config.version_details_query(),
+            models_query: config.models_query(),
+            model_count_query: config.model_count_query(),
+            payload_query: config.payload_query(),
+        }
+    }
+}
+
+impl ModelSource for PostgresModelSource {
+    fn list_versions<'a>(&'a self, model_id: &'a str) -> SourceFuture<'a, Vec<ModelVersionNumber>> {
+        Box::pin(async move {
+            let rows: Vec<(i32,)> = sqlx::query_as(&self.versions_query)
+                .bind(model_id)
+                .fetch_all(&self.pg_pool)
+                .await
+                .map_err(|e| ModelError::SourceError(e.to_string()))?;
+            Ok(rows
+                .into_iter()
+                .map(|(vers_no,)| ModelVersionNumber::new(vers_no))
+                .collect())
+        })
+    }
+
+    fn list_version_details<'a>(
+        &'a self,
+        model_id: &'a str,
+    ) -> SourceFuture<'a, Vec<ModelVersionNumber>> {
+        Box::pin(async move {
+            let rows: Vec<VersionRow> = sqlx::query_as(&self.version_details_query)
+                .bind(model_id)
+                .fetch_all(&self.pg_pool)
+                .await
+                .map_err(|e| ModelError::SourceError(e.to_string()))?;
+            Ok(rows
+                .into_iter()
+                .map(
+                    |(vers_no, payload_size, created_at, updated_at, schema_version)| {
+                        ModelVersionNumber {
+                            vers_no,
+                            payload_size,
+                            created_at,
+                            updated_at,
+                            schema_version,
+                        }
+                    },
+                )
+                .collect())
+        })
+    }
+
+    fn fetch_model<'a>(&'a self, model_id: &'a str, version: i32) -> SourceFuture<'a, Vec<u8>> {
+        Box::pin(async move {
+            let query = sqlx::query(&self.payload_query)
+                .bind(model_id)
+                .bind(version);
+            let row = query.fetch_one(&self.pg_pool).await.map_err(|e| match e {
+                sqlx::Error::RowNotFound => {
+                    ModelError::ModelNotFound(model_id.to_owned(), version.to_string())
+                }
+                e => ModelError::SourceError(e.to_string()),
+            })?;
+            let saved_model: Vec<u8> = match self.payload_encoding {
+                PayloadEncoding::Gzip => row.try_get::<Vec<u8>, _>(0),
+                PayloadEncoding::Json | PayloadEncoding::Jsonb => {
+                    row.try_get::<String, _>(0).map(String::into_bytes)
+                }
+            }
+            .map_err(|e| ModelError::SourceError(e.to_string()))?;
+            info!(
+                "[PostgresModelSource - fetch_model] Load saved model with model id: {} version: {} from DB",
+                model_id, version
+            );
+            Ok(saved_model)
+        })
+    }
+
+    fn list_models<'a>(
+        &'a self,
+        model_id_prefix: &'a str,
+        range: ModelRange,
+    ) -> SourceFuture<'a, SavedModelPage> {
+        Box::pin(async move {
+            let pattern = escape_like(model_id_prefix);
+            let (total_count,): (i64,) = sqlx::query_as(&self.model_count_query)
+                .bind(&pattern)
+                .fetch_one(&self.pg_pool)
+                .await
+                .map_err(|e| ModelError::SourceError(e.to_string()))?;
+            let to_i64 = |n: usize| i64::try_from(n).unwrap_or(i64::MAX);
+            let rows: Vec<(String, i64, i32)> = sqlx::query_as(&self.models_query)
+                .bind(&pattern)
+                .bind(range.limit.map(to_i64))
+                .bind(to_i64(range.offset))
+                .fetch_all(&self.pg_pool)
+                .await
+                .map_err(|e| ModelError::SourceError(e.to_string()))?;
+            Ok(SavedModelPage {
+                total_count: total_count as usize,
+                models: rows
+                    .into_iter()
+                    .map(
+                        |(model_id, version_count, latest_version)| SavedModelSummary {
+                            model_id,
+                            version_count: version_count as usize,
+                            latest_version,
+                        },
+                    )
+                    .collect(),
+            })
+        })
+    }
+
+    fn get_name(&self) -> &'static str {
+        "postgres"
+    }
+}
+
+// Local directory
+#[derive(Debug)]
+pub struct FileModelSource {
+    root: PathBuf,
+}
+
+impl FileModelSource {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    fn model_dir(&self, model_id: &str) -> Result<PathBuf, ModelError> {
+        validate_model_id(model_id)?;
+        Ok(self.root.join(model_id))
+    }
+}
+
+impl ModelSource for FileModelSource {
+    fn list_versions<'a>(&'a self, model_id: &'a str) -> SourceFuture<'a, Vec<ModelVersionNumber>> {
+        Box::pin(async move {
+            let model_dir = self.model_dir(model_id)?;
+            tokio::task::spawn_blocking(move || {
+                read_versions(&model_dir).map_err(|_| {
+                    ModelError::ModelNotFound(model_dir_name(&model_dir), "All".to_string())
+                })
+            })
+            .await
+            .map_err(|e| ModelError::SourceError(e.to_string()))?
+        })
+    }
+
+    fn fetch_model<'a>(&'a self, model_id: &'a str, version: i32) -> SourceFuture<'a, Vec<u8>> {
+        Box::pin(async move {
+            let model_dir = self.model_dir(model_id)?;
+            let model_id = model_id.to_owned();
+            tokio::task::spawn_blocking(move || {
+                for file_name in version_file_names(version) {
+                    match std::fs::read(model_dir.join(&file_name)) {
+                        Ok(bytes) => return Ok(bytes),
+                        Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                        Err(e) => return Err(ModelError::SourceError(e.to_string())),
+                    }
+                }
+                Err(ModelError::ModelNotFound(model_id, version.to_string()))
+            })
+            .await
+            .map_err(|e| ModelError::SourceError(e.to_string()))?
+        })
+    }
+
+    // Directories without a version file are not listed, every one is read
+    fn list_models<'a>(
+        &'a self,
+        model_id_prefix: &'a str,
+        range: ModelRange,
+    ) -> SourceFuture<'a, SavedModelPage> {
+        Box::pin(async move {
+            let root = self.root.clone();
+            let model_id_prefix = model_id_prefix.to_owned();
+            tokio::task::spawn_blocking(move || {
+                let entries =
+                    std::fs::read_dir(&root).map_err(|e| ModelError::SourceError(e.to_string()))?;
+                let mut models: Vec<SavedModelSummary> = entries
+                    .filter_map(|entry| entry.ok())
+                    .filter(|entry| entry.path().is_dir())
+                    .filter_map(|entry| {
+                        let model_id = entry.file_name().to_string_lossy().to_string();
+                        if !model_id.starts_with(&model_id_prefix)
+                            || validate_model_id(&model_id).is_err()
+                        {
+                            return None;
+                        }
+                        let versions: Vec<i32> = read_versions(&entry.path())
+                            .ok()?
+                            .iter()
+                            .map(|v| v.vers_no)
+                            .collect();
+                        SavedModelSummary::from_versions(model_id, &versions)
+                    })
+                    .collect();
+                models.sort_unstable_by(|a, b| a.model_id.cmp(&b.model_id));
+                Ok(SavedModelPage {
+                    total_count: models.len(),
+                    models: range.apply(models),
+                })
+            })
+            .await
+            .map_err(|e| ModelError::SourceError(e.to_string()))?
+        })
+    }
+
+    fn get_name(&self) -> &'static str {
+        "filesystem"
+    }
+}
+
+// HTTP endpoint
+#[derive(Debug)]
+pub struct HttpModelSource {
+    base_url: String,
+    client: reqwest::Client,
+}
+
+impl HttpModelSource {
+    pub fn new(base_url: String) -> Self {
+        Self {
+            base_url,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    // None when the file does not exist
+    async fn get(&self, path: &str) -> Result<Option<Vec<u8>>, ModelError> {
+        let url = format!("{}/{}", self.base_url, path);
+        let response = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| ModelError::SourceError(e.to_string()))?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let response = response
+            .error_for_status()
+            .map_err(|e| ModelError::SourceError(e.to_string()))?;
+        let bytes = response
+            .bytes()
+            .await
+            .map_err(|e| ModelError::SourceError(e.to_string()))?;
+        Ok(Some(bytes.to_vec()))
+    }
+}
+
+impl ModelSource for HttpModelSource {
+    fn list_versions<'a>(&'a self, model_id: &'a str) -> SourceFuture<'a, Vec<ModelVersionNumber>> {
+        Box::pin(async move {
+            validate_model_id(model_id)?;
+            let index = self.get(&format!("{}/index.json", model_id)).await?.ok_or(
+                ModelError::ModelNotFound(model_id.to_owned(), "All".to_string()),
+            )?;
+            let mut versions: Vec<i32> = serde_json::from_slice(&index)
+                .map_err(|e| ModelError::SourceError(e.to_string()))?;
+            versions.sort_unstable_by(|a, b| b.cmp(a));
+            Ok(versions.into_iter().map(ModelVersionNumber::new).collect())
+        })
+    }
+
+    fn fetch_model<'a>(&'a self, model_id: &'a str, version: i32) -> SourceFuture<'a, Vec<u8>> {
+        Box::pin(async move {
+            validate_model_id(model_id)?;
+            for file_name in version_file_names(version) {
+                if let Some(bytes) = self.get(&format!("{}/{}", model_id, file_name)).await? {
+                    return Ok(bytes);
+                }
+            }
+            Err(ModelError::ModelNotFound(
+                model_id.to_owned(),
+                version.to_string(),
+            ))
+        })
+    }
+
+    // Root index.json lists the model ids, versions are fetched for the range only
+    fn list_models<'a>(
+        &'a self,
+        model_id_prefix: &'a str,
+        range: ModelRange,
+    ) -> SourceFuture<'a, SavedModelPage> {
+        Box::pin(async move {
+            let index = self
+                .get("index.json")
+                .await?
+                .ok_or(ModelError::SourceError(format!(
+                    "{}/index.json not found",
+                    self.base_url
+                )))?;
+            let mut model_ids: Vec<String> = serde_json::from_slice(&index)
+                .map_err(|e| ModelError::SourceError(e.to_string()))?;
+            model_ids.retain(|id| id.starts_with(model_id_prefix));
+            model_ids.sort_unstable();
+            model_ids.dedup();
+            let total_count = model_ids.len();
+
+            let model_ids = range.apply(model_ids);
+            let mut models: Vec<SavedModelSummary> = Vec::with_capacity(model_ids.len());
+            for model_id in model_ids {
+                let versions: Vec<i32> = self
+                    .list_versions(&model_id)
+                    .await?
+                    .iter()
+                    .map(|v| v.vers_no)
+                    .collect();
+                models.extend(SavedModelSummary::from_versions(model_id, &versions));
+            }
+            Ok(SavedModelPage {
+                total_count,
+                models,
+            })
+        })
+    }
+
+    fn get_name(&self) -> &'static str {
+        "http"
+    }
+}
+
+// Single model file, used by the offline CLI
+#[derive(Debug)]
+pub struct ModelFileSource {
+    path: PathBuf,
+    model_id: String,
+    version: i32,
+}
+
+impl ModelFileSource {
+    // Decode once to learn the model id and version, the data is handed back to the caller
+    pub async fn open(path: PathBuf) -> Result<(Self, ModelData), ModelError> {
+        let model_data = parser::read_model_data_from_file(path.clone()).await?;
+        let source = Self {
+            path,
+            model_id: model_data.model_id.clone(),
+            version: model_data.version as i32,
+        };
+        Ok((source, model_data))
+    }
+
+    pub fn get_model_id(&self) -> &str {
+        &self.model_id
+    }
+
+    pub fn get_version(&self) -> i32 {
+        self.version
+    }
+
+    fn check(&self, model_id: &str, version: i32) -> Result<(), ModelError> {
+        match model_id == self.model_id && version == self.version {
+            true => Ok(()),
+            false => Err(ModelError::ModelNotFound(
+                model_id.to_owned(),
+                version.to_string(),
+            )),
+        }
+    }
+}
+
+impl ModelSource for ModelFileSource {
+    fn list_versions<'a>(&'a self, model_id: &'a str) -> SourceFuture<'a, Vec<ModelVersionNumber>> {
+        Box::pin(async move {
+            self.check(model_id, self.version)?;
+            Ok(vec![ModelVersionNumber::new(self.version)])
+        })
+    }
+
+    fn fetch_model<'a>(&'a self, model_id: &'a str, version: i32) -> SourceFuture<'a, Vec<u8>> {
+        Box::pin(async move {
+            self.check(model_id, version)?;
+            let path = self.path.clone();
+            tokio::task::spawn_blocking(move || std::fs::read(path))
+                .await
+                .map_err(|e| ModelError::SourceError(e.to_string()))?
+                .map_err(|e| ModelError::SourceError(e.to_string()))
+        })
+    }
+
+    fn list_models<'a>(
+        &'a self,
+        model_id_prefix: &'a str,
+        range: ModelRange,
+    ) -> SourceFuture<'a, SavedModelPage> {
+        Box::pin(async move {
+            let models: Vec<SavedModelSummary> =
+                SavedModelSummary::from_versions(self.model_id.clone(), &[self.version])
+                    .filter(|m| m.model_id.starts_with(model_id_prefix))
+                    .into_iter()
+                    .collect();
+            Ok(SavedModelPage {
+                total_count: models.len(),
+                models: range.apply(models),
+            })
+        })
+    }
+
+    fn get_name(&self) -> &'static str {
+        "file"
+    }
+}
+
+// Model id is used as a path segment
+pub fn validate_model_id(model_id: &str) -> Result<(), ModelError> {
+    let is_valid = !model_id.is_empty()
+        && model_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    match is_valid {
+        true => Ok(()),
+        false => Err(ModelError::InvalidInput(format!(
+            "Model id {} contains unsupported character",
+            model_id
+        ))),
+    }
+}
+
+// Prefix matched literally by LIKE, backslash being its default escape
+fn escape_like(prefix: &str) -> String {
+    prefix
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+// Gzipped file first
+fn version_file_names(version: i32) -> [String; 2] {
+    [format!("{}.json.gz", version), format!("{}.json", version)]
+}
+
+fn parse_version_file_name(file_name: &str) -> Option<i32> {
+    file_name
+        .strip_suffix(".json.gz")
+        .or_else(|| file_name.strip_suffix(".json"))
+        .and_then(|version| version.parse::<i32>().ok())
+}
+
+// Versions found in a model directory, latest first.
+// The size is the one of the file fetch_model reads, gzipped first
+fn read_versions(model_dir: &Path) -> std::io::Result<Vec<ModelVersionNumber>> {
+    let mut files: Vec<(i32, bool, Option<i64>)> = std::fs::read_dir(model_dir)?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            let version = parse_version_file_name(&file_name)?;
+            let size = entry.metadata().ok().map(|m| m.len() as i64);
+            Some((version, !file_name.ends_with(".gz"), size))
+        })
+        .collect();
+    files.sort_unstable_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+    files.dedup_by_key(|f| f.0);
+    Ok(files
+        .into_iter()
+        .map(|(vers_no, _, payload_size)| ModelVersionNumber {
+            vers_no,
+            payload_size,
+            ..Default::default()
+        })
+        .collect())
+}
+
+fn model_dir_name(model_dir: &Path) -> String {
+    model_dir
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_version_file_name() {
+        assert_eq!(parse_version_file_name("3.json.gz"), Some(3));
+        assert_eq!(parse_version_file_name("12.json"), Some(12));
+        assert_eq!(parse_version_file_name("index.json"), None);
+        assert_eq!(parse_version_file_name("3.txt"), None);
+        assert!(validate_model_id("a1b2-c3_d4").is_ok());
+        assert!(validate_model_id("../etc").is_err());
+    }
+
+    #[test]
+    fn test_postgres_table_config() {
+        let config = PostgresTableConfig::default();
+        assert!(config.validate().is_ok());
+        assert_eq!(
+            config.versions_query(),
+            r#"SELECT "vers_no"::int4 FROM "cubs_object_model"."saved_model" WHERE "model_id" = $1 ORDER BY "vers_no" DESC"#
+        );
+        assert_eq!(
+            config.version_details_query(),
+            r#"SELECT "vers_no"::int4, pg_column_size("saved_gzip")::int8, NULL::text, NULL::text, NULL::text FROM "cubs_object_model"."saved_model" WHERE "model_id" = $1 ORDER BY "vers_no" DESC"#
+        );
+        assert_eq!(
+            config.payload_query(),
+            r#"SELECT "saved_gzip" FROM "cubs_object_model"."saved_model" WHERE "model_id" = $1 and "vers_no" = $2"#
+        );
+
+        assert_eq!(
+            config.models_query(),
+            r#"SELECT "model_id"::text, count(*)::int8, max("vers_no")::int4 FROM "cubs_object_model"."saved_model" WHERE "model_id"::text LIKE $1 || '%' GROUP BY "model_id" ORDER BY "model_id" LIMIT $2 OFFSET $3"#
+        );
+        assert_eq!(
+            config.model_count_query(),
+            r#"SELECT count(DISTINCT "model_id")::int8 FROM "cubs_object_model"."saved_model" WHERE "model_id"::text LIKE $1 || '%'"#
+        );
+        assert_eq!(escape_like(r"a_b%c\d"), r"a\_b\%c\\d");
+
+        let config = PostgresTableConfig {
+            schema: "public".to_string(),
+            table: "models".to_string(),
+            payload_column: "body".to_string(),
+            payload_encoding: PayloadEncoding::parse("JSONB").unwrap(),
+            ..PostgresTableConfig::default()
+        };
+        assert_eq!(
+            config.payload_query(),
+            r#"SELECT "body"::text FROM "public"."models" WHERE "model_id" = $1 and "vers_no" = $2"#
+        );
+        assert_eq!(
+            config.versions_query(),
+            r#"SELECT "vers_no"::int4 FROM "public"."models" WHERE "model_id" = $1 ORDER BY "vers_no" DESC"#
+        );
+        assert_eq!(
+            config.version_details_query(),
+            r#"SELECT "vers_no"::int4, pg_column_size("body")::int8, NULL::text, NULL::text, "body"->>'schemaVersion' FROM "public"."models" WHERE "model_id" = $1 ORDER BY "vers_no" DESC"#
+        );
+
+        let config = PostgresTableConfig {
+            created_at_column: Some("created".to_string()),
+            schema_version_column: Some("schema_vers".to_string()),
+            ..PostgresTableConfig::default()
+        };
+        assert_eq!(
+            config.version_details_query(),
+            r#"SELECT "vers_no"::int4, pg_column_size("saved_gzip")::int8, "created"::text, NULL::text, "schema_vers"::text FROM "cubs_object_model"."saved_model" WHERE "model_id" = $1 ORDER BY "vers_no" DESC"#
+        );
+
+        let config = PostgresTableConfig {
+            table: "models\"; DROP TABLE x; --".to_string(),
+            ..PostgresTableConfig::default()
+        };
+        assert!(config.validate().is_err());
+        let config = PostgresTableConfig {
+            updated_at_column: Some("updated at".to_string()),
+            ..PostgresTableConfig::default()
+        };
+        assert!(config.validate().is_err());
+        assert!(PayloadEncoding::parse("xml").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_file_model_source() {
+        let root = std::env::temp_dir().join(format!("model_source_test_{}", std::process::id()));
+        let model_dir = root.join("m1");
+        std::fs::create_dir_all(&model_dir).unwrap();
+        std::fs::write(model_dir.join("1.json"), b"{}").unwrap();
+        std::fs::write(model_dir.join("2.json.gz"), b"gzip").unwrap();
+        std::fs::write(model_dir.join("notes.txt"), b"").unwrap();
+
+        let source = FileModelSource::new(root.clone());
+        let versions: Vec<i32> = source
+            .list_versions("m1")
+            .await
+            .unwrap()
+            .iter()
+            .map(|v| v.vers_no)
+            .collect();
+        assert_eq!(versions, vec![2, 1]);
+        assert_eq!(
+            source.list_versions("m1").await.unwrap()[0].payload_size,
+            Some(4)
+        );
+        assert_eq!(source.fetch_model("m1", 2).await.unwrap(), b"gzip");
+        assert_eq!(source.fetch_model("m1", 1).await.unwrap(), b"{}");
+        assert!(matches!(
+            source.fetch_model("m1", 3).await,
+            Err(ModelError::ModelNotFound(_, _))
+        ));
+        assert!(source.list_versions("m2").await.is_err());
+
+        std::fs::create_dir_all(root.join("m2")).unwrap();
+        std::fs::create_dir_all(root.join("x1")).unwrap();
+        std::fs::write(root.join("x1").join("7.json"), b"{}").unwrap();
+        let page = source.list_models("", ModelRange::default()).await.unwrap();
+        assert_eq!(page.total_count, 2);
+        assert_eq!(
+            page.models,
+            vec![
+                SavedModelSummary {
+                    model_id: "m1".to_string(),
+                    version_count: 2,
+                    latest_version: 2
+                },
+                SavedModelSummary {
+                    model_id: "x1".to_string(),
+                    version_count: 1,
+                    latest_version: 7
+                },
+            ]
+        );
+        assert_eq!(
+            source
+                .list_models("x", ModelRange::default())
+                .await
+                .unwrap()
+                .models
+                .len(),
+            1
+        );
+        let range = ModelRange {
+            offset: 1,
+            limit: Some(1),
+        };
+        let page = source.list_models("", range).await.unwrap();
+        assert_eq!(page.total_count, 2);
+        assert_eq!(page.models[0].model_id, "x1");
+        let range = ModelRange {
+            offset: 2,
+            limit: None,
+        };
+        let page = source.list_models("", range).await.unwrap();
+        assert!(page.models.is_empty());
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_model_file_source() {
+        let path =
+            std::env::temp_dir().join(format!("model_file_test_{}.json", std::process::id()));
+        let json = r#"{"schemaVersion": "1", "modelId": "m1", "siteModelId": "s1", "version": 4,
+            "elements": [], "relationships": []}"#;
+        std::fs::write(&path, json).unwrap();
+
+        let (source, model_data) = ModelFileSource::open(path.clone()).await.unwrap();
+        assert_eq!(model_data.model_id, "m1");
+        assert_eq!(source.get_version(), 4);
+        assert_eq!(source.list_versions("m1").await.unwrap()[0].vers_no, 4);
+        assert_eq!(source.fetch_model("m1", 4).await.unwrap(), json.as_bytes());
+        assert!(source.fetch_model("m1", 3).await.is_err());
+        assert!(source.list_versions("m2").await.is_err());
+        let page = source
+            .list_models("m", ModelRange::default())
+            .await
+            .unwrap();
+        assert_eq!(page.models[0].latest_version, 4);
+        let page = source
+            .list_models("x", ModelRange::default())
+            .await
+            .unwrap();
+        assert_eq!(page.total_count, 0);
+
+        std::fs::remove_file(&path).unwrap();
+        assert!(ModelFileSource::open(path).await.is_err());
+    }
+}
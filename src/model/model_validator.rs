@@ -0,0 +1,276 @@
+use std::collections::{HashMap, HashSet};
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+
+use crate::model::{cubs_model::ModelData, utils::Utils};
+
+// Consistency checks on a loaded ModelData
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ValidationCheck {
+    DuplicateElementId,
+    DuplicateRelationshipId,
+    UnknownSourceElement,
+    UnknownTargetElement,
+    MissingType,
+    MissingNature,
+    EmptyName,
+    VersionAfterModel,
+    SelfReference,
+}
+
+impl ValidationCheck {
+    pub fn get_severity(&self) -> Severity {
+        match self {
+            ValidationCheck::DuplicateElementId
+            | ValidationCheck::DuplicateRelationshipId
+            | ValidationCheck::UnknownSourceElement
+            | ValidationCheck::UnknownTargetElement
+            | ValidationCheck::MissingType
+            | ValidationCheck::MissingNature => Severity::Error,
+            ValidationCheck::EmptyName
+            | ValidationCheck::VersionAfterModel
+            | ValidationCheck::SelfReference => Severity::Warning,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidationFinding {
+    pub severity: Severity,
+    pub check: ValidationCheck,
+    pub message: String,
+    pub element_ids: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub relationship_id: Option<String>,
+}
+
+pub struct ModelValidator;
+
+impl ModelValidator {
+    // Findings ordered by severity, then in model order
+    pub fn validate(model: &ModelData) -> Vec<ValidationFinding> {
+        let start_time = Instant::now();
+        let mut findings: Vec<ValidationFinding> = Vec::new();
+        let mut add = |check: ValidationCheck,
+                       message: String,
+                       element_ids: Vec<String>,
+                       relationship_id: Option<&str>| {
+            findings.push(ValidationFinding {
+                severity: check.get_severity(),
+                check,
+                message,
+                element_ids,
+                relationship_id: relationship_id.map(str::to_owned),
+            });
+        };
+
+        // Elements
+        let mut element_count: HashMap<&str, usize> = HashMap::new();
+        for element in &model.elements {
+            *element_count.entry(element.id.as_str()).or_default() += 1;
+        }
+        let mut reported: HashSet<&str> = HashSet::new();
+        for element in &model.elements {
+            let id = element.id.as_str();
+            let count = element_count[id];
+            if count > 1 && reported.insert(id) {
+                add(
+                    ValidationCheck::DuplicateElementId,
+                    format!("Element id {} is used by {} elements", id, count),
+                    vec![id.to_owned()],
+                    None,
+                );
+            }
+            if element.type_.trim().is_empty() {
+                add(
+                    ValidationCheck::MissingType,
+                    format!("Element {} has no type", id),
+                    vec![id.to_owned()],
+                    None,
+                );
+            }
+            if element.nature.trim().is_empty() {
+                add(
+                    ValidationCheck::MissingNature,
+                    format!("Element {} has no nature", id),
+                    vec![id.to_owned()],
+                    None,
+                );
+            }
+            if element.name.trim().is_empty() {
+                add(
+                    ValidationCheck::EmptyName,
+                    format!("Element {} has an empty name", id),
+                    vec![id.to_owned()],
+                    None,
+                );
+            }
+            if element.version > model.version {
+                add(
+                    ValidationCheck::VersionAfterModel,
+                    format!(
+                        "Element {} version {} is after model version {}",
+                        id, element.version, model.version
+                    ),
+                    vec![id.to_owned()],
+                    None,
+                );
+            }
+        }
+
+        // Relationships, names are optional
+        let mut relationship_count: HashMap<&str, usize> = HashMap::new();
+        for relationship in &model.relationships {
+            *relationship_count
+                .entry(relationship.id.as_str())
+                .or_default() += 1;
+        }
+        let mut reported: HashSet<&str> = HashSet::new();
+        for relationship in &model.relationships {
+            let id = relationship.id.as_str();
+            let ends = vec![
+                relationship.source_id.clone(),
+                relationship.target_id.clone(),
+            ];
+            let count = relationship_count[id];
+            if count > 1 && reported.insert(id) {
+                add(
+                    ValidationCheck::DuplicateRelationshipId,
+                    format!("Relationship id {} is used by {} relationships", id, count),
+                    ends.clone(),
+                    Some(id),
+                );
+            }
+            if !element_count.contains_key(relationship.source_id.as_str()) {
+                add(
+                    ValidationCheck::UnknownSourceElement,
+                    format!(
+                        "Relationship {} source {} is not an element",
+                        id, relationship.source_id
+                    ),
+                    vec![relationship.source_id.clone()],
+                    Some(id),
+                );
+            }
+            if !element_count.contains_key(relationship.target_id.as_str()) {
+                add(
+                    ValidationCheck::UnknownTargetElement,
+                    format!(
+                        "Relationship {} target {} is not an element",
+                        id, relationship.target_id
+                    ),
+                    vec![relationship.target_id.clone()],
+                    Some(id),
+                );
+            }
+            if relationship.type_.trim().is_empty() {
+                add(
+                    ValidationCheck::MissingType,
+                    format!("Relationship {} has no type", id),
+                    ends.clone(),
+                    Some(id),
+                );
+            }
+            if relationship.nature.trim().is_empty() {
+                add(
+                    ValidationCheck::MissingNature,
+                    format!("Relationship {} has no nature", id),
+                    ends.clone(),
+                    Some(id),
+                );
+            }
+            if relationship.version > model.version {
+                add(
+                    ValidationCheck::VersionAfterModel,
+                    format!(
+                        "Relationship {} version {} is after model version {}",
+                        id, relationship.version, model.version
+                    ),
+                    ends.clone(),
+                    Some(id),
+                );
+            }
+            if relationship.source_id == relationship.target_id {
+                add(
+                    ValidationCheck::SelfReference,
+                    format!(
+                        "Relationship {} connects {} to itself",
+                        id, relationship.source_id
+                    ),
+                    vec![relationship.source_id.clone()],
+                    Some(id),
+                );
+            }
+        }
+
+        // Stable, keeps model order within a severity
+        findings.sort_by_key(|f| f.severity);
+        Utils::log_time(start_time, "ModelValidator - validate");
+        findings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::test_fixture;
+    use serde_json::json;
+
+    fn build_model() -> ModelData {
+        test_fixture::build_model(
+            3,
+            json!([
+                {"id": "p1", "type": "Pump", "nature": "Asset", "name": "P1", "version": 1, "facets": {}},
+                {"id": "p1", "type": "Pump", "nature": "Asset", "name": "P1 copy", "version": 1, "facets": {}},
+                {"id": "t1", "type": "", "nature": "Asset", "name": " ", "version": 4, "facets": {}}
+            ]),
+            json!([
+                {"id": "r1", "sourceId": "p1", "targetId": "t1", "type": "feeds", "nature": "Flow", "version": 1, "facets": {}},
+                {"id": "r1", "sourceId": "t1", "targetId": "x1", "type": "feeds", "nature": "", "version": 1, "facets": {}},
+                {"id": "r2", "sourceId": "t1", "targetId": "t1", "type": "feeds", "nature": "Flow", "version": 1, "facets": {}}
+            ]),
+        )
+    }
+
+    #[test]
+    fn test_validate() {
+        let findings = ModelValidator::validate(&build_model());
+        let checks: Vec<(Severity, ValidationCheck)> =
+            findings.iter().map(|f| (f.severity, f.check)).collect();
+        assert_eq!(
+            checks,
+            vec![
+                (Severity::Error, ValidationCheck::DuplicateElementId),
+                (Severity::Error, ValidationCheck::MissingType),
+                (Severity::Error, ValidationCheck::DuplicateRelationshipId),
+                (Severity::Error, ValidationCheck::UnknownTargetElement),
+                (Severity::Error, ValidationCheck::MissingNature),
+                (Severity::Warning, ValidationCheck::EmptyName),
+                (Severity::Warning, ValidationCheck::VersionAfterModel),
+                (Severity::Warning, ValidationCheck::SelfReference),
+            ]
+        );
+        assert_eq!(findings[0].element_ids, vec!["p1"]);
+        assert_eq!(findings[3].element_ids, vec!["x1"]);
+        assert_eq!(findings[3].relationship_id.as_deref(), Some("r1"));
+        assert_eq!(findings[7].relationship_id.as_deref(), Some("r2"));
+    }
+
+    #[test]
+    fn test_validate_clean_model() {
+        let mut model = build_model();
+        model.elements.truncate(1);
+        model.relationships.clear();
+        assert!(ModelValidator::validate(&model).is_empty());
+    }
+}
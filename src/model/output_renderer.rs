@@ -0,0 +1,210 @@
+use std::fmt::Write;
+
+use serde_json::Value;
+
+use crate::model::config::{OutputGraph, OutputLine, OutputToken, RelationshipDirection};
+
+// Render OutputGraph tokens into an indented text tree
+
+pub struct OutputGraphRenderer;
+
+struct RenderLine<'a> {
+    level: usize,
+    relationship_id: Option<&'a str>,
+    element_id: Option<&'a str>,
+}
+
+impl OutputGraphRenderer {
+    pub fn render_tree(output: &OutputGraph<String>) -> String {
+        let mut rendered = String::new();
+
+        // Parent lines are stored from the furthest ancestor, render them from the target
+        let parent_lines: Vec<&OutputLine<String>> = output.parent_lines.iter().rev().collect();
+        if parent_lines.len() > 1 {
+            rendered.push_str("Ancestors:\n");
+            Self::render_lines(
+                output,
+                &parent_lines,
+                &RelationshipDirection::Parent,
+                &mut rendered,
+            );
+            rendered.push_str("Descendants:\n");
+        }
+
+        let child_lines: Vec<&OutputLine<String>> = output.child_lines.iter().collect();
+        Self::render_lines(
+            output,
+            &child_lines,
+            &RelationshipDirection::Child,
+            &mut rendered,
+        );
+
+        rendered
+    }
+
+    fn render_lines(
+        output: &OutputGraph<String>,
+        lines: &[&OutputLine<String>],
+        direction: &RelationshipDirection,
+        rendered: &mut String,
+    ) {
+        let lines: Vec<RenderLine> = lines.iter().map(|l| Self::read_line(l)).collect();
+
+        for (i, line) in lines.iter().enumerate() {
+            // Vertical bar for every ancestor level still having sibling below
+            for level in 1..line.level {
+                match Self::has_sibling_below(&lines, i, level) {
+                    true => rendered.push_str("│   "),
+                    false => rendered.push_str("    "),
+                }
+            }
+
+            if line.level > 0 {
+                match Self::has_sibling_below(&lines, i, line.level) {
+                    true => rendered.push_str("├── "),
+                    false => rendered.push_str("└── "),
+                }
+                let relationship = line
+                    .relationship_id
+                    .map(|id| Self::relationship_label(output, id))
+                    .unwrap_or_default();
+                match direction {
+                    RelationshipDirection::Parent => {
+                        let _ = write!(rendered, "{} ──◄ ", relationship);
+                    }
+                    RelationshipDirection::Child => {
+                        let _ = write!(rendered, "{} ──► ", relationship);
+                    }
+                }
+            }
+
+            let element = line
+                .element_id
+                .map(|id| Self::element_label(output, id))
+                .unwrap_or_default();
+            rendered.push_str(&element);
+            rendered.push('\n');
+        }
+    }
+
+    fn read_line(line: &OutputLine<String>) -> RenderLine<'_> {
+        let mut render_line = RenderLine {
+            level: 0,
+            relationship_id: None,
+            element_id: None,
+        };
+        for token in &line.line {
+            match token {
+                OutputToken::Tab => render_line.level += 1,
+                OutputToken::Relationship(id) => render_line.relationship_id = Some(id),
+                OutputToken::Value(id) => render_line.element_id = Some(id),
+                OutputToken::InArrow | OutputToken::OutArrow => {}
+            }
+        }
+        render_line
+    }
+
+    // Another line at the same level before the tree goes back above it
+    fn has_sibling_below(lines: &[RenderLine], index: usize, level: usize) -> bool {
+        lines[index + 1..]
+            .iter()
+            .take_while(|l| l.level >= level)
+            .any(|l| l.level == level)
+    }
+
+    fn element_label(output: &OutputGraph<String>, id: &str) -> String {
+        match output.elements_data.get(id) {
+            Some(data) => format!(
+                "{} ({}) [{}]",
+                Self::get_field(data, "name"),
+                Self::get_field(data, "type"),
+                id
+            ),
+            None => format!("[{}]", id),
+        }
+    }
+
+    fn relationship_label(output: &OutputGraph<String>, id: &str) -> String {
+        match output.relationships_data.get(id) {
+            Some(data) => Self::get_field(data, "type").to_owned(),
+            None => id.to_owned(),
+        }
+    }
+
+    fn get_field<'a>(data: &'a Value, field: &str) -> &'a str {
+        data.get(field).and_then(|v| v.as_str()).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    fn line(level: usize, relationship_id: Option<&str>, element_id: &str) -> OutputLine<String> {
+        let mut line = OutputLine::new();
+        for _ in 0..level {
+            line.push(OutputToken::Tab);
+        }
+        if level > 0 {
+            line.push(OutputToken::OutArrow);
+        }
+        if let Some(id) = relationship_id {
+            line.push(OutputToken::Relationship(id.to_string()));
+        }
+        line.push(OutputToken::Value(element_id.to_string()));
+        line
+    }
+
+    #[test]
+    fn test_render_tree() {
+        let output = OutputGraph {
+            parent_lines: vec![line(0, None, "c1")],
+            child_lines: vec![
+                line(0, None, "c1"),
+                line(1, Some("r1"), "c2"),
+                line(2, Some("r2"), "c3"),
+                line(1, Some("r3"), "c4"),
+            ],
+            elements_data: HashMap::from([
+                (
+                    "c1".to_string(),
+                    json!({"id": "c1", "name": "Pump 1", "type": "Pump"}),
+                ),
+                (
+                    "c2".to_string(),
+                    json!({"id": "c2", "name": "Tank 1", "type": "Tank"}),
+                ),
+            ]),
+            relationships_data: HashMap::from([(
+                "r1".to_string(),
+                json!({"id": "r1", "type": "feeds"}),
+            )]),
+        };
+
+        let rendered = OutputGraphRenderer::render_tree(&output);
+        let expected = "Pump 1 (Pump) [c1]\n\
+                        ├── feeds ──► Tank 1 (Tank) [c2]\n\
+                        │   └── r2 ──► [c3]\n\
+                        └── r3 ──► [c4]\n";
+        assert_eq!(rendered, expected);
+    }
+
+    #[test]
+    fn test_render_tree_with_ancestors() {
+        let output = OutputGraph {
+            // Stored from the furthest ancestor
+            parent_lines: vec![line(1, Some("r0"), "p1"), line(0, None, "c1")],
+            child_lines: vec![line(0, None, "c1")],
+            elements_data: HashMap::new(),
+            relationships_data: HashMap::new(),
+        };
+
+        let rendered = OutputGraphRenderer::render_tree(&output);
+        assert_eq!(
+            rendered,
+            "Ancestors:\n[c1]\n└── r0 ──◄ [p1]\nDescendants:\n[c1]\n"
+        );
+    }
+}
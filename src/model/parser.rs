@@ -1,158 +1,236 @@
-use crate::model::app_state::QuickCache;
-
 use super::cubs_model::{ModelData, ModelVersionNumber};
-use anyhow::anyhow;
+use super::model_error::ModelError;
+use super::model_migration::ModelMigration;
+use super::model_source::ModelSource;
 use flate2::bufread::GzDecoder;
+use serde::de::IgnoredAny;
+use std::collections::HashMap;
 use std::error::Error;
-use std::sync::Arc;
+use std::path::PathBuf;
 use std::time::Instant;
-use std::io::Read;
-use uuid::Uuid;
-#[derive(Debug, sqlx::FromRow)]
-struct SavedModel {
-    pub model_id: String,
-    pub vers_no: i32,
-    pub saved_gzip: Vec<u8>,
-}
+use std::io::BufReader;
+
+use crate::info;
 
 pub async fn read_model_data_versions(
-    pg_pool: &sqlx::Pool<sqlx::Postgres>,
+    model_source: &dyn ModelSource,
     model_id: &String,
 ) -> Result<Vec<ModelVersionNumber>, Box<dyn Error>> {
     let start_time = Instant::now();
 
-    println!(
-        "[read_model_data_versions] Retrieving {} model version from DB...",
-        &model_id
+    info!(
+        "[read_model_data_versions] Retrieving {} model version from {}...",
+        &model_id,
+        model_source.get_name()
     );
 
-    // Retrieve from DB
-    let model_versions = sqlx::query_as!(
-        ModelVersionNumber,
-        r#"SELECT vers_no FROM cubs_object_model.saved_model WHERE model_id = $1 ORDER BY vers_no DESC"#,
-        model_id
-    )
-    .fetch_all(pg_pool)
-    .await?;
+    // Retrieve from source
+    let model_versions = model_source.list_versions(model_id).await?;
 
     //Log time
     let elapsed_time = start_time.elapsed();
-    println!(
-        "[Execution time] {} - {:?}",
-        "read_model_data_versions", elapsed_time
+    info!(
+        "[Execution time] read_model_data_versions - {:?}", elapsed_time
     );
 
     Ok(model_versions)
 }
 
-async fn read_model_data_from_db_with_version(
-    pg_pool: &sqlx::Pool<sqlx::Postgres>,
+pub async fn read_model_data_version_details(
+    model_source: &dyn ModelSource,
     model_id: &String,
-    version_no: i32,
-    cache: &QuickCache<ModelData>,
-) -> Result<ModelData, Box<dyn Error>> {
+) -> Result<Vec<ModelVersionNumber>, Box<dyn Error>> {
     let start_time = Instant::now();
 
-    println!(
-        "[read_model_data_from_db_with_version] Retrieving {} model from DB...",
-        &model_id
-    );
-
-    // Retrieve from DB
-    println!("[read_model_data_from_db_with_version] Retreiving from DB ...");
-    let saved_model = sqlx::query_as!(
-        SavedModel,
-        r#"SELECT model_id, vers_no, saved_gzip FROM cubs_object_model.saved_model WHERE model_id = $1 and vers_no = $2"#,
-        model_id, version_no
-    )
-    .fetch_one(pg_pool)
-    .await?;
-    println!(
-        "[read_model_data_from_db_with_version]  Load saved model with model id: {} version: {} from DB",
-        saved_model.model_id, saved_model.vers_no
+    info!(
+        "[read_model_data_version_details] Retrieving {} model version details from {}...",
+        &model_id,
+        model_source.get_name()
     );
 
-    // Unzip
-    println!("[read_model_data_from_db_with_version] Unzip ...");
-    let decompressed_model = decompress_gzip_to_string(&saved_model.saved_gzip)?;
-
-    //Convert to ModelData
-    println!("[read_model_data_from_db_with_version] Convert to internal format ...");
-    let model_data: ModelData = serde_json::from_str(&decompressed_model)?;
-
-    // Store in cache
-    let key = model_id.clone() + "_" + &model_data.version.to_string();
-    println!(
-        "[read_model_data_from_db_with_version] Cache Model data key: {}",
-        key
-    );
-    cache.insert(
-        &model_id.clone(),
-        &model_data.version.to_string(),
-        &model_data,
-    );
+    // Payload size and metadata, only read for the model stats
+    let model_versions = model_source.list_version_details(model_id).await?;
 
     //Log time
     let elapsed_time = start_time.elapsed();
-    println!(
-        "[Execution time] {} - {:?}",
-        "read_model_data_from_db_with_version + cache", elapsed_time
+    info!(
+        "[Execution time] read_model_data_version_details - {:?}", elapsed_time
     );
 
-    Ok(model_data)
+    Ok(model_versions)
 }
 
-pub fn get_model_from_cache(
-    cache: &QuickCache<ModelData>,
+pub async fn read_model_data_with_version(
+    model_source: &dyn ModelSource,
     model_id: &String,
-    version_num: i32,
-) -> Option<Arc<ModelData>> {
+    version_no: i32,
+) -> Result<ModelData, ModelError> {
     let start_time = Instant::now();
 
-    println!(
-        "[get_model_from_cache] Retrievig {} model with version {:?} from cache.",
-        &model_id, version_num
+    info!(
+        "[read_model_data_with_version] Retrieving {} model version {} from {}...",
+        &model_id,
+        version_no,
+        model_source.get_name()
+    );
+
+    // Retrieve from source
+    let saved_model = model_source.fetch_model(model_id, version_no).await?;
+    info!(
+        "[read_model_data_with_version]  Load saved model with model id: {} version: {} ({} bytes)",
+        model_id, version_no, saved_model.len()
+    );
+    info!(
+        "[Execution time] read_model_data_with_version - fetch - {:?}", start_time.elapsed()
+    );
+
+    // Unzip and convert to ModelData in one pass, CPU bound
+    info!("[read_model_data_with_version] Unzip and convert to internal format ...");
+    let decode_start_time = Instant::now();
+    let model_data = tokio::task::spawn_blocking(move || decode_model_data(&saved_model))
+        .await
+        .map_err(|e| ModelError::SourceError(e.to_string()))??;
+    info!(
+        "[Execution time] read_model_data_with_version - decode - {:?}", decode_start_time.elapsed()
     );
 
-    let cached_model_data = cache.get_ref(&model_id.clone(), &version_num.to_string());
     //Log time
     let elapsed_time = start_time.elapsed();
-    println!(
-        "[Execution time] {} - {:?}",
-        "get_model_from_cache", elapsed_time
+    info!(
+        "[Execution time] read_model_data_with_version - total - {:?}", elapsed_time
     );
 
-    cached_model_data
+    Ok(model_data)
 }
 
-pub async fn get_model_from_db(
-    pg_pool: &sqlx::Pool<sqlx::Postgres>,
-    cache: &QuickCache<ModelData>,
-    model_id: &String,
-    version_num: i32,
-) -> Option<Arc<ModelData>> {
+pub async fn read_model_data_from_file(path: PathBuf) -> Result<ModelData, ModelError> {
     let start_time = Instant::now();
-
-    // G_model_data
-    let _model_data =
-        read_model_data_from_db_with_version(pg_pool, model_id, version_num, &cache).await;
-
-    // Get reference from cache
-    let cached_model_data = cache.get_ref(&model_id.clone(), &version_num.to_string());
-
-    //Log time
-    let elapsed_time = start_time.elapsed();
-    println!(
-        "[Execution time] {} - {:?}",
-        "read_model_data", elapsed_time
+    info!("[read_model_data_from_file] Reading {} ...", path.display());
+
+    // File read and decode are both blocking
+    let saved_model = tokio::task::spawn_blocking(move || {
+        std::fs::read(&path).map_err(|e| ModelError::SourceError(format!("{}: {}", path.display(), e)))
+    })
+    .await
+    .map_err(|e| ModelError::SourceError(e.to_string()))??;
+    let model_data = tokio::task::spawn_blocking(move || decode_model_data(&saved_model))
+        .await
+        .map_err(|e| ModelError::SourceError(e.to_string()))??;
+
+    info!(
+        "[Execution time] read_model_data_from_file - {:?}", start_time.elapsed()
     );
+    Ok(model_data)
+}
+
+// Top level keys are read first with the values skipped, the current layout is then
+// deserialized from the gzip stream without holding the whole JSON text and an older
+// layout goes through a Value to be upgraded by ModelMigration
+fn decode_model_data(saved_model: &[u8]) -> Result<ModelData, ModelError> {
+    let keys = deserialize::<HashMap<String, IgnoredAny>>(saved_model)
+        .map_err(|e| ModelError::ParsingError(format!("model data, {}", e)))?;
+    if !ModelMigration::is_legacy_layout(keys.keys().map(String::as_str)) {
+        return deserialize::<ModelData>(saved_model)
+            .map_err(|e| ModelError::ParsingError(format!("model data, {}", e)));
+    }
+
+    info!("[decode_model_data] Older layout, migrating ...");
+    let value = deserialize::<serde_json::Value>(saved_model)
+        .map_err(|e| ModelError::ParsingError(format!("model data, {}", e)))?;
+    ModelMigration::migrate(value)
+}
 
-    cached_model_data
+// Sources may also serve plain JSON
+fn deserialize<T: serde::de::DeserializeOwned>(saved_model: &[u8]) -> Result<T, serde_json::Error> {
+    if saved_model.starts_with(&[0x1f, 0x8b]) {
+        let decoder = GzDecoder::new(saved_model);
+        serde_json::from_reader(BufReader::new(decoder))
+    } else {
+        serde_json::from_slice(saved_model)
+    }
 }
 
-fn decompress_gzip_to_string(gzip: &Vec<u8>) -> Result<String, Box<dyn Error>> {
-    let mut decoder = GzDecoder::new(gzip.as_slice());
-    let mut decompressed_data = String::new();
-    decoder.read_to_string(&mut decompressed_data)?;
-    Ok(decompressed_data)
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::{Compression, write::GzEncoder};
+    use std::io::Write;
+
+    #[test]
+    fn test_decode_model_data() {
+        let json = r#"{"schemaVersion": "1", "modelId": "m1", "siteModelId": "s1", "version": 2,
+            "elements": [{"id": "p1", "type": "Pump", "nature": "Asset", "name": "P1", "version": 1, "facets": {}}],
+            "relationships": []}"#;
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(json.as_bytes()).unwrap();
+        let gzip = encoder.finish().unwrap();
+
+        let model_data = decode_model_data(&gzip).unwrap();
+        assert_eq!(model_data.model_id, "m1");
+        assert_eq!(model_data.version, 2);
+        assert_eq!(model_data.elements[0].id, "p1");
+
+        assert!(decode_model_data(&gzip[..gzip.len() / 2]).is_err());
+
+        // Plain JSON
+        let model_data = decode_model_data(json.as_bytes()).unwrap();
+        assert_eq!(model_data.model_id, "m1");
+        assert!(decode_model_data(b"not json").is_err());
+
+        // Older layout is upgraded
+        let legacy = r#"{"modelId": "m1", "siteModelId": "s1", "version": 2,
+            "cubsObjects": [{"id": "p1", "type": "Pump", "nature": "Asset", "name": "P1", "version": 1, "facets": {}}]}"#;
+        let model_data = decode_model_data(legacy.as_bytes()).unwrap();
+        assert_eq!(model_data.schema_version, "");
+        assert_eq!(model_data.elements[0].id, "p1");
+    }
+
+    #[test]
+    fn test_decode_stored_model() {
+        // Current layout loads whatever schemaVersion it is stamped with
+        let json = r#"{"schemaVersion": "2.3", "modelId": "a1b2-c3", "siteModelId": "site-9", "version": 14,
+            "elements": [
+                {"id": "p1", "type": "Pump", "nature": "Asset", "name": "P1", "version": 3, "serialNumber": "SN-1",
+                    "facets": {"design": {"pressure": {"value": 4.5, "unit": "bar"}}},
+                    "dynamicFacets": {"status": {"value": "running"}}},
+                {"id": "t1", "type": "Tank", "nature": "Asset", "name": "T1", "version": 1, "facets": {}}
+            ],
+            "relationships": [
+                {"id": "r1", "sourceId": "p1", "targetId": "t1", "type": "feeds", "nature": "Flow", "version": 1,
+                    "facets": {"flowRate": {"value": 12.5}}}
+            ]}"#;
+        for schema_version in ["2.3", "1", "v5", ""] {
+            let json = json.replace(r#""schemaVersion": "2.3""#, &format!(r#""schemaVersion": "{}""#, schema_version));
+            let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(json.as_bytes()).unwrap();
+            let model_data = decode_model_data(&encoder.finish().unwrap()).unwrap();
+            assert_eq!(model_data.schema_version, schema_version);
+            assert_eq!(model_data.elements.len(), 2);
+            assert_eq!(model_data.relationships[0].target_id, "t1");
+            assert_eq!(model_data.elements[0].core_facets["serialNumber"], "SN-1");
+        }
+    }
+
+    #[test]
+    fn test_decode_model_data_error() {
+        // Current layout keeps its decode error instead of migrating
+        let json = r#"{"schemaVersion": "1", "modelId": "m1", "siteModelId": "s1", "version": 2,
+            "elements": [{"id": "p1", "type": "Pump", "nature": "Asset", "name": "P1", "version": "x", "facets": {}}],
+            "relationships": []}"#;
+        let expected = serde_json::from_str::<ModelData>(json).unwrap_err().to_string();
+        match decode_model_data(json.as_bytes()) {
+            Err(ModelError::ParsingError(msg)) => assert_eq!(msg, format!("model data, {}", expected)),
+            other => panic!("unexpected {:?}", other),
+        }
+
+        // Truncated or invalid JSON is not migrated either
+        assert!(matches!(
+            decode_model_data(&json.as_bytes()[..40]),
+            Err(ModelError::ParsingError(_))
+        ));
+        assert!(matches!(
+            decode_model_data(b"not json"),
+            Err(ModelError::ParsingError(_))
+        ));
+    }
 }
@@ -0,0 +1,608 @@
+use std::time::Instant;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::model::{
+    config::TraversalDirection,
+    cubs_model::{CusObject, Element, Relationship},
+    element_graph::ElementGraph,
+    model_error::ModelError,
+    model_snapshot::ModelSnapshot,
+    utils::Utils,
+};
+
+// Small Cypher-like pattern query over ElementGraph joined with the ModelSnapshot
+//
+//  (a:Pump)-[:feeds]->(b:Tank)-[r:containedIn]->(c {name:"Area X"})
+//
+// Node label matches the element type, relationship label matches the relationship type
+// (several types separated with |). Properties match id, name, type, nature or a top level
+// facet key. Direction can be ->, <- or - for both.
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodePattern {
+    pub variable: Option<String>,
+    pub label: Option<String>,
+    pub properties: Vec<(String, Value)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RelationshipPattern {
+    pub variable: Option<String>,
+    pub types: Vec<String>,
+    pub properties: Vec<(String, Value)>,
+    pub direction: TraversalDirection,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PatternQuery {
+    pub start: NodePattern,
+    pub steps: Vec<(RelationshipPattern, NodePattern)>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PatternBinding {
+    Element {
+        id: String,
+        #[serde(rename = "type")]
+        type_: String,
+        nature: String,
+        name: String,
+    },
+    Relationship {
+        id: String,
+        #[serde(rename = "type")]
+        type_: String,
+        nature: String,
+        name: String,
+        source_id: String,
+        target_id: String,
+    },
+}
+
+pub type PatternRow = Vec<(String, PatternBinding)>;
+
+pub struct PatternMatcher<'a> {
+    query: &'a PatternQuery,
+    graph: &'a ElementGraph,
+    snapshot: &'a ModelSnapshot,
+    limit: usize,
+}
+
+// Parser
+struct PatternParser<'a> {
+    input: &'a str,
+    chars: Vec<char>,
+    position: usize,
+}
+
+impl PatternQuery {
+    pub fn parse(query: &str) -> Result<PatternQuery, ModelError> {
+        let mut parser = PatternParser {
+            input: query,
+            chars: query.chars().collect(),
+            position: 0,
+        };
+
+        let start = parser.parse_node()?;
+        let mut steps = Vec::new();
+        loop {
+            parser.skip_whitespace();
+            if parser.is_end() {
+                break;
+            }
+            let relationship = parser.parse_relationship()?;
+            let node = parser.parse_node()?;
+            steps.push((relationship, node));
+        }
+
+        Ok(PatternQuery { start, steps })
+    }
+
+    pub fn get_variables(&self) -> Vec<String> {
+        let mut variables: Vec<String> = Vec::new();
+        let mut push = |v: &Option<String>| {
+            if let Some(v) = v
+                && !variables.contains(v)
+            {
+                variables.push(v.clone());
+            }
+        };
+        push(&self.start.variable);
+        for (relationship, node) in &self.steps {
+            push(&relationship.variable);
+            push(&node.variable);
+        }
+        variables
+    }
+}
+
+impl<'a> PatternParser<'a> {
+    fn parse_node(&mut self) -> Result<NodePattern, ModelError> {
+        self.expect('(')?;
+        let variable = self.parse_identifier();
+        let label = match self.consume(':') {
+            true => Some(self.parse_name()?),
+            false => None,
+        };
+        let properties = self.parse_properties()?;
+        self.expect(')')?;
+
+        Ok(NodePattern {
+            variable,
+            label,
+            properties,
+        })
+    }
+
+    fn parse_relationship(&mut self) -> Result<RelationshipPattern, ModelError> {
+        let is_incoming = self.consume('<');
+        self.expect('-')?;
+
+        let mut variable = None;
+        let mut types = Vec::new();
+        let mut properties = Vec::new();
+        if self.consume('[') {
+            variable = self.parse_identifier();
+            if self.consume(':') {
+                types.push(self.parse_name()?);
+                while self.consume('|') {
+                    self.consume(':');
+                    types.push(self.parse_name()?);
+                }
+            }
+            properties = self.parse_properties()?;
+            self.expect(']')?;
+        }
+        self.expect('-')?;
+        let is_outgoing = self.consume('>');
+
+        let direction = match (is_incoming, is_outgoing) {
+            (false, true) => TraversalDirection::Out,
+            (true, false) => TraversalDirection::In,
+            (false, false) => TraversalDirection::Both,
+            (true, true) => return Err(self.error("relationship cannot point both ways")),
+        };
+
+        Ok(RelationshipPattern {
+            variable,
+            types,
+            properties,
+            direction,
+        })
+    }
+
+    fn parse_properties(&mut self) -> Result<Vec<(String, Value)>, ModelError> {
+        let mut properties = Vec::new();
+        if !self.consume('{') {
+            return Ok(properties);
+        }
+        if self.consume('}') {
+            return Ok(properties);
+        }
+        loop {
+            let key = self.parse_name()?;
+            self.expect(':')?;
+            let value = self.parse_value()?;
+            properties.push((key, value));
+            if self.consume('}') {
+                return Ok(properties);
+            }
+            self.expect(',')?;
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<Value, ModelError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(quote) if quote == '"' || quote == '\'' => {
+                self.position += 1;
+                let mut value = String::new();
+                loop {
+                    match self.next() {
+                        Some('\\') => match self.next() {
+                            Some(c) => value.push(c),
+                            None => return Err(self.error("unterminated string")),
+                        },
+                        Some(c) if c == quote => return Ok(Value::String(value)),
+                        Some(c) => value.push(c),
+                        None => return Err(self.error("unterminated string")),
+                    }
+                }
+            }
+            _ => {
+                let literal = self.take_while(|c| !matches!(c, ',' | '}') && !c.is_whitespace());
+                serde_json::from_str(&literal)
+                    .map_err(|_| self.error(&format!("invalid value {}", literal)))
+            }
+        }
+    }
+
+    fn parse_identifier(&mut self) -> Option<String> {
+        self.skip_whitespace();
+        let identifier = self.take_while(|c| c.is_alphanumeric() || c == '_');
+        match identifier.is_empty() {
+            true => None,
+            false => Some(identifier),
+        }
+    }
+
+    // Name allowing quoted form with backtick for types containing special characters
+    fn parse_name(&mut self) -> Result<String, ModelError> {
+        self.skip_whitespace();
+        if self.consume('`') {
+            let name = self.take_while(|c| c != '`');
+            self.expect('`')?;
+            return Ok(name);
+        }
+        let name = self.take_while(|c| c.is_alphanumeric() || matches!(c, '_' | '.' | '$'));
+        match name.is_empty() {
+            true => Err(self.error("expected a name")),
+            false => Ok(name),
+        }
+    }
+
+    fn take_while<F: Fn(char) -> bool>(&mut self, predicate: F) -> String {
+        let start = self.position;
+        while self.peek().is_some_and(&predicate) {
+            self.position += 1;
+        }
+        self.chars[start..self.position].iter().collect()
+    }
+
+    fn consume(&mut self, expected: char) -> bool {
+        self.skip_whitespace();
+        if self.peek() == Some(expected) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), ModelError> {
+        match self.consume(expected) {
+            true => Ok(()),
+            false => Err(self.error(&format!("expected '{}'", expected))),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(|c| c.is_whitespace()) {
+            self.position += 1;
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek();
+        self.position += 1;
+        c
+    }
+
+    fn is_end(&self) -> bool {
+        self.position >= self.chars.len()
+    }
+
+    fn error(&self, msg: &str) -> ModelError {
+        ModelError::InvalidInput(format!(
+            "Pattern {} {} at position {}",
+            self.input, msg, self.position
+        ))
+    }
+}
+
+// Matcher
+impl<'a> PatternMatcher<'a> {
+    pub fn new(
+        query: &'a PatternQuery,
+        graph: &'a ElementGraph,
+        snapshot: &'a ModelSnapshot,
+        limit: usize,
+    ) -> Self {
+        PatternMatcher {
+            query,
+            graph,
+            snapshot,
+            limit,
+        }
+    }
+
+    // Return the matched rows and whether the search stopped at the limit
+    pub fn find(&self) -> (Vec<PatternRow>, bool) {
+        let start_time = Instant::now();
+        let mut rows: Vec<PatternRow> = Vec::new();
+
+        // Label from type index
+        let mut candidates: Vec<&Element> = self
+            .snapshot
+            .get_elements_with_type_nature(self.query.start.label.as_deref(), None)
+            .into_iter()
+            .filter(|e| Self::is_node_matching(&self.query.start, e))
+            .collect();
+        candidates.sort_by(|a, b| a.id.cmp(&b.id));
+
+        let mut bindings: Vec<(Option<&str>, &str, bool)> = Vec::new();
+        for element in candidates {
+            if self.is_full(&rows) {
+                break;
+            }
+            bindings.push((self.query.start.variable.as_deref(), &element.id, false));
+            self.extend(0, &element.id, &mut bindings, &mut rows);
+            bindings.pop();
+        }
+
+        // One row past the limit tells a truncated search from an exact fit
+        let is_truncated = rows.len() > self.limit;
+        rows.truncate(self.limit);
+        Utils::log_time(start_time, "PatternMatcher - find");
+        (rows, is_truncated)
+    }
+
+    // Depth first extension of the current partial match.
+    // Binding: (variable, id, is relationship)
+    fn extend(
+        &self,
+        step: usize,
+        current_id: &'a str,
+        bindings: &mut Vec<(Option<&'a str>, &'a str, bool)>,
+        rows: &mut Vec<PatternRow>,
+    ) {
+        if self.is_full(rows) {
+            return;
+        }
+        let Some((relationship_pattern, node_pattern)) = self.query.steps.get(step) else {
+            rows.push(self.build_row(bindings));
+            return;
+        };
+        let Some(connector) = self.graph.get_connection(current_id) else {
+            return;
+        };
+
+        let out_paths = connector.get_out_id().into_iter();
+        let in_paths = connector.get_in_id().into_iter();
+        let paths: Vec<_> = match relationship_pattern.direction {
+            TraversalDirection::Out => out_paths.collect(),
+            TraversalDirection::In => in_paths.collect(),
+            TraversalDirection::Both => out_paths.chain(in_paths).collect(),
+        };
+
+        for path in paths {
+            let (relationship_id, next_id) = (path.get_relationship_id(), path.get_element_id());
+            let Some(relationship) = self.snapshot.get_relationship(relationship_id) else {
+                continue;
+            };
+            let Some(element) = self.snapshot.get_element(next_id) else {
+                continue;
+            };
+            if !Self::is_relationship_matching(relationship_pattern, relationship)
+                || !Self::is_node_matching(node_pattern, element)
+                || !Self::is_binding_consistent(
+                    bindings,
+                    relationship_pattern.variable.as_deref(),
+                    relationship_id,
+                )
+                || !Self::is_binding_consistent(bindings, node_pattern.variable.as_deref(), next_id)
+            {
+                continue;
+            }
+
+            bindings.push((
+                relationship_pattern.variable.as_deref(),
+                relationship_id,
+                true,
+            ));
+            bindings.push((node_pattern.variable.as_deref(), next_id, false));
+            self.extend(step + 1, next_id, bindings, rows);
+            bindings.pop();
+            bindings.pop();
+
+            if self.is_full(rows) {
+                return;
+            }
+        }
+    }
+
+    fn is_full(&self, rows: &[PatternRow]) -> bool {
+        rows.len() > self.limit
+    }
+
+    fn build_row(&self, bindings: &[(Option<&str>, &str, bool)]) -> PatternRow {
+        let mut row: PatternRow = Vec::new();
+        for (variable, id, is_relationship) in bindings {
+            let Some(variable) = variable else {
+                continue;
+            };
+            if row.iter().any(|(v, _)| v == variable) {
+                continue;
+            }
+            let binding = if *is_relationship {
+                self.snapshot
+                    .get_relationship(id)
+                    .map(|r| PatternBinding::Relationship {
+                        id: r.id.clone(),
+                        type_: r.type_.clone(),
+                        nature: r.nature.clone(),
+                        name: r.name.clone(),
+                        source_id: r.source_id.clone(),
+                        target_id: r.target_id.clone(),
+                    })
+            } else {
+                self.snapshot
+                    .get_element(id)
+                    .map(|e| PatternBinding::Element {
+                        id: e.id.clone(),
+                        type_: e.type_.clone(),
+                        nature: e.nature.clone(),
+                        name: e.name.clone(),
+                    })
+            };
+            if let Some(binding) = binding {
+                row.push((variable.to_string(), binding));
+            }
+        }
+        row
+    }
+
+    fn is_binding_consistent(
+        bindings: &[(Option<&str>, &str, bool)],
+        variable: Option<&str>,
+        id: &str,
+    ) -> bool {
+        match variable {
+            Some(variable) => bindings
+                .iter()
+                .filter(|(v, _, _)| *v == Some(variable))
+                .all(|(_, bound_id, _)| *bound_id == id),
+            None => true,
+        }
+    }
+
+    fn is_node_matching(pattern: &NodePattern, element: &Element) -> bool {
+        pattern.label.as_ref().is_none_or(|l| *l == element.type_)
+            && Self::is_properties_matching(&pattern.properties, element)
+    }
+
+    fn is_relationship_matching(
+        pattern: &RelationshipPattern,
+        relationship: &Relationship,
+    ) -> bool {
+        (pattern.types.is_empty() || pattern.types.contains(&relationship.type_))
+            && Self::is_properties_matching(&pattern.properties, relationship)
+    }
+
+    fn is_properties_matching<T: CusObject>(properties: &[(String, Value)], obj: &T) -> bool {
+        properties.iter().all(|(key, expected)| {
+            let value = match key.as_str() {
+                "id" => Some(Value::String(obj.get_id())),
+                "name" => Some(Value::String(obj.get_name())),
+                "type" => Some(Value::String(obj.get_type())),
+                "nature" => Some(Value::String(obj.get_nature())),
+                "version" => Some(Value::from(obj.get_version())),
+                _ => obj
+                    .get_core_facet()
+                    .get(key)
+                    .or_else(|| obj.get_facet().get(key))
+                    .or_else(|| obj.get_dynamic_facet().get(key))
+                    .cloned(),
+            };
+            value.as_ref() == Some(expected)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::cubs_model::ModelData;
+    use crate::model::test_fixture;
+    use serde_json::json;
+
+    fn build_model() -> ModelData {
+        test_fixture::build_model(
+            1,
+            json!([
+                {"id": "p1", "type": "Pump", "nature": "Asset", "name": "P1", "version": 1, "facets": {}},
+                {"id": "p2", "type": "Pump", "nature": "Asset", "name": "P2", "version": 1, "facets": {}},
+                {"id": "t1", "type": "Tank", "nature": "Asset", "name": "T1", "version": 1, "facets": {}},
+                {"id": "t2", "type": "Tank", "nature": "Asset", "name": "T2", "version": 1, "facets": {}},
+                {"id": "a1", "type": "Area", "nature": "Location", "name": "Area X", "version": 1, "facets": {}},
+                {"id": "a2", "type": "Area", "nature": "Location", "name": "Area Y", "version": 1, "facets": {}}
+            ]),
+            json!([
+                {"id": "r1", "sourceId": "p1", "targetId": "t1", "type": "feeds", "nature": "Flow", "version": 1, "facets": {}},
+                {"id": "r2", "sourceId": "p2", "targetId": "t2", "type": "feeds", "nature": "Flow", "version": 1, "facets": {}},
+                {"id": "r3", "sourceId": "t1", "targetId": "a1", "type": "containedIn", "nature": "Containment", "version": 1, "facets": {}},
+                {"id": "r4", "sourceId": "t2", "targetId": "a2", "type": "containedIn", "nature": "Containment", "version": 1, "facets": {}}
+            ]),
+        )
+    }
+
+    #[test]
+    fn test_parse() {
+        let query = PatternQuery::parse(
+            r#"(a:Pump)-[:feeds]->(b:Tank)<-[r:containedIn|:has]-(c {name:"Area X", version: 1})"#,
+        )
+        .unwrap();
+        assert_eq!(query.start.variable.as_deref(), Some("a"));
+        assert_eq!(query.start.label.as_deref(), Some("Pump"));
+        assert_eq!(query.steps.len(), 2);
+        assert_eq!(query.steps[0].0.direction, TraversalDirection::Out);
+        assert_eq!(query.steps[1].0.direction, TraversalDirection::In);
+        assert_eq!(query.steps[1].0.types, vec!["containedIn", "has"]);
+        assert_eq!(
+            query.steps[1].1.properties[0],
+            ("name".to_string(), json!("Area X"))
+        );
+        assert_eq!(
+            query.steps[1].1.properties[1],
+            ("version".to_string(), json!(1))
+        );
+        assert_eq!(query.get_variables(), vec!["a", "b", "r", "c"]);
+
+        assert!(PatternQuery::parse("(a:Pump)-[:feeds]->").is_err());
+        assert!(PatternQuery::parse("(a:Pump").is_err());
+        assert!(PatternQuery::parse(r#"(a {name:"x)"#).is_err());
+    }
+
+    #[test]
+    fn test_find() {
+        let snapshot = ModelSnapshot::build(build_model());
+        let graph = snapshot.get_graph().unwrap();
+
+        let query = PatternQuery::parse(
+            r#"(a:Pump)-[:feeds]->(b:Tank)-[r:containedIn]->(c {name:"Area X"})"#,
+        )
+        .unwrap();
+        let (rows, is_truncated) = PatternMatcher::new(&query, graph, &snapshot, 100).find();
+        assert!(!is_truncated);
+        assert_eq!(rows.len(), 1);
+
+        let variables: Vec<&str> = rows[0].iter().map(|(v, _)| v.as_str()).collect();
+        assert_eq!(variables, vec!["a", "b", "r", "c"]);
+        match &rows[0][0].1 {
+            PatternBinding::Element { id, .. } => assert_eq!(id, "p1"),
+            _ => panic!("Expected element"),
+        }
+    }
+
+    #[test]
+    fn test_find_both_direction_and_limit() {
+        let snapshot = ModelSnapshot::build(build_model());
+        let graph = snapshot.get_graph().unwrap();
+
+        let query = PatternQuery::parse("(t:Tank)-[:feeds]-(p)").unwrap();
+        let (rows, _) = PatternMatcher::new(&query, graph, &snapshot, 100).find();
+        assert_eq!(rows.len(), 2);
+
+        let query = PatternQuery::parse("(x)--(y)").unwrap();
+        let (rows, is_truncated) = PatternMatcher::new(&query, graph, &snapshot, 3).find();
+        assert_eq!(rows.len(), 3);
+        assert!(is_truncated);
+
+        // Exactly as many rows as the limit
+        let query = PatternQuery::parse("(p:Pump)-[:feeds]->(t)").unwrap();
+        let (rows, is_truncated) = PatternMatcher::new(&query, graph, &snapshot, 2).find();
+        assert_eq!(rows.len(), 2);
+        assert!(!is_truncated);
+    }
+
+    #[test]
+    fn test_find_with_version() {
+        let snapshot = ModelSnapshot::build(build_model());
+        let graph = snapshot.get_graph().unwrap();
+
+        let query = PatternQuery::parse("(p:Pump {version: 1})-[:feeds]->(t)").unwrap();
+        let (rows, _) = PatternMatcher::new(&query, graph, &snapshot, 100).find();
+        assert_eq!(rows.len(), 2);
+
+        let query = PatternQuery::parse("(p:Pump {version: 2})-[:feeds]->(t)").unwrap();
+        let (rows, _) = PatternMatcher::new(&query, graph, &snapshot, 100).find();
+        assert!(rows.is_empty());
+    }
+}
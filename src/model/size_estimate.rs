@@ -0,0 +1,158 @@
+use std::collections::HashMap;
+use std::mem::size_of;
+
+use serde_json::Value;
+
+use crate::model::{
+    cubs_model::{Element, ModelData, Relationship},
+    element_graph_centrality::CentralityReport,
+};
+
+// Approximate heap bytes, used to weigh cache entries against the memory budget.
+// Allocator overhead and spare Vec capacity are not counted.
+
+pub trait EstimateSize {
+    fn estimate_size(&self) -> usize;
+}
+
+// hashbrown keeps 1/8 of the buckets free, bucket count is a power of two
+pub fn hash_map_size(len: usize, entry_size: usize) -> usize {
+    let buckets = match len {
+        0 => return 0,
+        1..=3 => 4,
+        4..=7 => 8,
+        _ => (len * 8 / 7).next_power_of_two(),
+    };
+    buckets * (entry_size + 1) + 16
+}
+
+// serde_json objects are BTreeMap, nodes of 11 entries assumed 2/3 full
+fn object_size(len: usize) -> usize {
+    len.div_ceil(7) * (11 * size_of::<(String, Value)>() + 16)
+}
+
+// Heap owned by the value, its own size is counted by its container
+pub fn value_heap_size(value: &Value) -> usize {
+    match value {
+        Value::Null | Value::Bool(_) | Value::Number(_) => 0,
+        Value::String(s) => s.len(),
+        Value::Array(values) => {
+            values.len() * size_of::<Value>() + values.iter().map(value_heap_size).sum::<usize>()
+        }
+        Value::Object(map) => {
+            object_size(map.len())
+                + map
+                    .iter()
+                    .map(|(k, v)| k.len() + value_heap_size(v))
+                    .sum::<usize>()
+        }
+    }
+}
+
+pub fn map_size(map: &HashMap<String, Value>) -> usize {
+    hash_map_size(map.len(), size_of::<(String, Value)>())
+        + map
+            .iter()
+            .map(|(k, v)| k.len() + value_heap_size(v))
+            .sum::<usize>()
+}
+
+impl EstimateSize for Element {
+    fn estimate_size(&self) -> usize {
+        size_of::<Element>()
+            + self.id.len()
+            + self.type_.len()
+            + self.nature.len()
+            + self.name.len()
+            + self.dynamic_facets.estimate_size()
+            + self.facets.estimate_size()
+            + map_size(&self.core_facets)
+    }
+}
+
+impl EstimateSize for Relationship {
+    fn estimate_size(&self) -> usize {
+        size_of::<Relationship>()
+            + self.id.len()
+            + self.source_id.len()
+            + self.target_id.len()
+            + self.type_.len()
+            + self.nature.len()
+            + self.name.len()
+            + map_size(&self.dynamic_facets)
+            + map_size(&self.facets)
+            + map_size(&self.core_facets)
+    }
+}
+
+impl EstimateSize for ModelData {
+    fn estimate_size(&self) -> usize {
+        size_of::<ModelData>()
+            + self.schema_version.len()
+            + self.model_id.len()
+            + self.site_model_id.len()
+            + self
+                .elements
+                .iter()
+                .map(EstimateSize::estimate_size)
+                .sum::<usize>()
+            + self
+                .relationships
+                .iter()
+                .map(EstimateSize::estimate_size)
+                .sum::<usize>()
+    }
+}
+
+impl EstimateSize for CentralityReport {
+    fn estimate_size(&self) -> usize {
+        size_of::<CentralityReport>()
+            + self
+                .scores
+                .iter()
+                .map(|s| size_of_val(s) + s.element_id.len())
+                .sum::<usize>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::test_fixture;
+    use serde_json::json;
+
+    #[test]
+    fn test_value_size() {
+        assert_eq!(value_heap_size(&json!(1)), 0);
+        assert_eq!(value_heap_size(&json!("abc")), 3);
+        assert_eq!(
+            value_heap_size(&json!([1, "ab"])),
+            2 * size_of::<Value>() + 2
+        );
+        assert_eq!(
+            value_heap_size(&json!({"a": [true]})),
+            object_size(1) + 1 + size_of::<Value>()
+        );
+        assert_eq!(hash_map_size(0, 8), 0);
+        assert_eq!(hash_map_size(7, 8), 8 * 9 + 16);
+        assert_eq!(hash_map_size(8, 8), 16 * 9 + 16);
+    }
+
+    #[test]
+    fn test_model_size() {
+        let model: ModelData = test_fixture::build_model(
+            1,
+            json!([
+                {"id": "p1", "type": "Pump", "nature": "Asset", "name": "P1", "version": 1, "facets": {"a": "x".repeat(1000)}}
+            ]),
+            json!([]),
+        );
+        let size = model.estimate_size();
+        assert!(size > 1000 && size < 2000 + size_of::<Element>());
+
+        // Parsing lazy facets is counted once the map exists
+        let mut parsed = model.clone();
+        parsed.parse_facets();
+        assert!(parsed.estimate_size() > 1000);
+    }
+}
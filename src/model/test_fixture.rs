@@ -0,0 +1,30 @@
+use serde_json::{Value, json};
+
+use crate::model::{cubs_model::ModelData, element_graph::ElementGraph};
+
+// Graphs and models shared by the unit tests
+
+// Connector for every id, then one connection per (relationship id, source id, target id)
+pub fn build_graph(ids: &[&str], relationships: &[(&str, &str, &str)]) -> ElementGraph {
+    let mut graph = ElementGraph::new();
+    for id in ids {
+        graph.add_connector(id);
+    }
+    for (relationship_id, source_id, target_id) in relationships {
+        graph.connect(relationship_id, source_id, target_id);
+    }
+    graph
+}
+
+// Model m1 of site s1 holding the given json elements and relationships
+pub fn build_model(version: u32, elements: Value, relationships: Value) -> ModelData {
+    serde_json::from_value(json!({
+        "schemaVersion": "1",
+        "modelId": "m1",
+        "siteModelId": "s1",
+        "version": version,
+        "elements": elements,
+        "relationships": relationships
+    }))
+    .unwrap()
+}
@@ -1,10 +1,34 @@
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Instant;
 
+// Logs go to stdout, stderr once the CLI keeps stdout for its output
+static LOG_TO_STDERR: AtomicBool = AtomicBool::new(false);
+
 pub struct Utils;
 
 impl Utils {
     pub fn log_time(from: Instant, msg: &str) {
         let elapse_time = from.elapsed();
-        println!("[Execution time] {} took - {:?}", msg, elapse_time);
+        crate::info!("[Execution time] {} took - {:?}", msg, elapse_time);
+    }
+
+    pub fn set_log_to_stderr() {
+        LOG_TO_STDERR.store(true, Ordering::Relaxed);
     }
+
+    pub fn is_log_to_stderr() -> bool {
+        LOG_TO_STDERR.load(Ordering::Relaxed)
+    }
+}
+
+// println! to the log output, named like the log crate macro so rustfmt lays it out as println!
+#[macro_export]
+macro_rules! info {
+    ($($arg:tt)*) => {
+        if $crate::model::utils::Utils::is_log_to_stderr() {
+            eprintln!($($arg)*);
+        } else {
+            println!($($arg)*);
+        }
+    };
 }
@@ -1,19 +1,23 @@
 #![allow(dead_code)]
-use std::result;
+use std::env;
 
 use model_parser_mcp::model::{
-    app_state::{self, AppState},
-    config::PageConfig,
+    app_state::AppState,
+    cache_admin::CacheAdmin,
+    config::{PageConfig, TraversalDirection},
     cubs_model::ModelVersionNumber,
-    model_parser::ModelParser,
+    element_graph_centrality::CentralityMetric,
+    graph_export::{FacetAttribute, GraphExportFormat},
+    model_error::ModelError,
+    output_renderer::OutputGraphRenderer,
 };
 use rmcp::{
     ServerHandler,
     handler::server::{
         router::tool::ToolRouter,
-        wrapper::{Json, Parameters},
+        wrapper::Parameters,
     },
-    model::{CallToolResult, ServerCapabilities, ServerInfo},
+    model::{ServerCapabilities, ServerInfo},
     schemars, tool, tool_handler, tool_router,
 };
 use schemars::JsonSchema;
@@ -25,6 +29,12 @@ use serde::{Deserialize, Serialize};
 static EMPTY: &str = "";
 static ALL: &str = "All";
 static MAX_DEPTH: usize = 20;
+static EXPORT_DIR: &str = "export";
+static MAX_IMPACT_DEPTH: usize = 99;
+static LINEAGE_ANCESTOR_DEPTH: u32 = 2;
+static LINEAGE_CHILDREN_DEPTH: u32 = 5;
+// Only listed when CACHE_ADMIN_ENABLED is set
+static CACHE_ADMIN_TOOLS: [&str; 3] = ["get_cache_status", "evict_cached_model", "preload_model"];
 
 #[derive(Clone)]
 pub struct ModelParserTool {
@@ -103,17 +113,215 @@ struct ElementFilter {
     nature: String,
 }
 
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ModelExportRequest {
+    #[schemars(description = "Unique identifier for a model in the format of UUID")]
+    model_id: String,
+    #[schemars(description = "Model version")]
+    version_number: Option<String>,
+    #[schemars(description = "Export format. Either graphml or jgf")]
+    format: String,
+    #[schemars(
+        description = "Facet values flattened as node/edge attributes in the format <facetType>:<json pointer>. For example dynamicFacets:/flow/rate"
+    )]
+    facet_attributes: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ModelCentralityRequest {
+    #[schemars(description = "Unique identifier for a model in the format of UUID")]
+    model_id: String,
+    #[schemars(description = "Model version")]
+    version_number: Option<String>,
+    #[schemars(
+        description = "Centrality metric to rank with. One of degree, in_degree, out_degree, betweenness or pagerank"
+    )]
+    metric: String,
+    #[schemars(description = "Number of top ranked elements to retrieve")]
+    top_n: usize,
+    #[schemars(description = "Filter for specific type. To allow all, set to All")]
+    type_: String,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ModelLayeringRequest {
+    #[schemars(description = "Unique identifier for a model in the format of UUID")]
+    model_id: String,
+    #[schemars(description = "Model version")]
+    version_number: Option<String>,
+    #[schemars(
+        description = "Relationship types forming the DAG, for example process flow or containment. To allow all, set to []"
+    )]
+    relationship_types: Vec<String>,
+    #[schemars(description = "Include elements not connected by any of the relationship types")]
+    include_isolated: Option<bool>,
+    #[schemars(description = "Result pagination configuration")]
+    page_config: PageConfig,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ModelCommonAncestorRequest {
+    #[schemars(description = "Unique identifier for a model in the format of UUID")]
+    model_id: String,
+    #[schemars(description = "Model version")]
+    version_number: Option<String>,
+    #[schemars(description = "Two or more element ids to find the shared ancestors of")]
+    element_ids: Vec<String>,
+    #[schemars(
+        description = "Relationship types to follow upward, for example containment. To allow all, set to []"
+    )]
+    relationship_types: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ModelImpactRequest {
+    #[schemars(description = "Unique identifier for a model in the format of UUID")]
+    model_id: String,
+    #[schemars(description = "Model version")]
+    version_number: Option<String>,
+    #[schemars(description = "Id of the changed element")]
+    element_id: String,
+    #[schemars(description = "Relationship types to follow downstream. To allow all, set to []")]
+    relationship_types: Vec<String>,
+    #[schemars(description = "Maximum number of relationship hops to follow")]
+    depth: Option<usize>,
+    #[schemars(description = "Result pagination configuration")]
+    page_config: PageConfig,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ModelLineageRequest {
+    #[schemars(description = "Unique identifier for a model in the format of UUID")]
+    model_id: String,
+    #[schemars(description = "Model version")]
+    version_number: Option<String>,
+    #[schemars(description = "Id of the element to get the lineage of")]
+    element_id: String,
+    #[schemars(description = "Number of ancestor levels to include, at most 20")]
+    ancestor_depth: Option<u32>,
+    #[schemars(description = "Number of children levels to include, at most 20")]
+    children_depth: Option<u32>,
+    #[schemars(
+        description = "Output mode. Either json for the raw output tokens or tree for an indented text tree"
+    )]
+    output_mode: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ModelPatternQueryRequest {
+    #[schemars(description = "Unique identifier for a model in the format of UUID")]
+    model_id: String,
+    #[schemars(description = "Model version")]
+    version_number: Option<String>,
+    #[schemars(
+        description = "Cypher-like pattern. Node label is the element type, relationship label is the relationship type. For example (a:Pump)-[:feeds]->(b:Tank)-[:containedIn]->(c {name:\"Area X\"})"
+    )]
+    pattern: String,
+    #[schemars(description = "Result pagination configuration")]
+    page_config: PageConfig,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ModelNeighbourRequest {
+    #[schemars(description = "Unique identifier for a model in the format of UUID")]
+    model_id: String,
+    #[schemars(description = "Model version")]
+    version_number: Option<String>,
+    #[schemars(description = "Id of the element to get the neighbours of")]
+    element_id: String,
+    #[schemars(
+        description = "Relationship direction to follow. in for parents, out for children or both"
+    )]
+    direction: TraversalDirection,
+    #[schemars(description = "Maximum number of relationship hops from the element, at most 20")]
+    max_hop: Option<usize>,
+    #[schemars(description = "Result pagination configuration")]
+    page_config: PageConfig,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ModelListRequest {
+    #[schemars(description = "Only list model ids starting with this prefix")]
+    model_id_prefix: Option<String>,
+    #[schemars(
+        description = "Only list models of this site model id. Only models already loaded know their site model id"
+    )]
+    site_model_id: Option<String>,
+    #[schemars(description = "Result pagination configuration")]
+    page_config: PageConfig,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ModelValidationRequest {
+    #[schemars(description = "Unique identifier for a model in the format of UUID")]
+    model_id: String,
+    #[schemars(description = "Model version")]
+    version_number: Option<String>,
+    #[schemars(description = "Result pagination configuration")]
+    page_config: PageConfig,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ModelRelationshipFacetRequest {
+    #[schemars(description = "Unique identifier for a model in the format of UUID")]
+    model_id: String,
+    #[schemars(description = "Model version")]
+    version_number: Option<String>,
+    #[schemars(description = "Only relationships with this element as source or target")]
+    element_id: Option<String>,
+    #[schemars(description = "Filter for specific relationship type. To allow all, set to All")]
+    type_: String,
+    #[schemars(description = "Filter for specific relationship nature. To allow all, set to All")]
+    nature: String,
+    #[schemars(description = "Facets to query, one of coreFacets, dynamicFacets or facets")]
+    facet_type: String,
+    #[schemars(
+        description = "Json pointer applied to the facets, e.g. /flowRate/value. Set to \"\" to return the relationships with only the selected facets"
+    )]
+    query: String,
+    #[schemars(description = "Return the relationship id, type, source and target with each value")]
+    is_detail: bool,
+    #[schemars(description = "Truncate values deeper than depth, 0 keeps everything")]
+    depth: Option<usize>,
+    #[schemars(description = "Result pagination configuration")]
+    page_config: PageConfig,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct CacheEvictRequest {
+    #[schemars(description = "Unique identifier for a model in the format of UUID")]
+    model_id: String,
+    #[schemars(description = "Cached model version to evict, latest or empty for the latest one")]
+    version_number: String,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct CachePreloadRequest {
+    #[schemars(description = "Unique identifier for a model in the format of UUID")]
+    model_id: String,
+    #[schemars(description = "Model version, latest when not set")]
+    version_number: Option<String>,
+}
+
 #[tool_router]
 impl ModelParserTool {
     pub fn new(app_state: AppState) -> Self {
         // let app_state = AppState::global();
+        let mut tool_router = Self::tool_router();
+        if !app_state.is_cache_admin_enabled() {
+            for name in CACHE_ADMIN_TOOLS {
+                tool_router.remove_route(name);
+            }
+        }
         Self {
-            tool_router: Self::tool_router(),
-            app_state: app_state,
+            tool_router,
+            app_state,
         }
     }
 
-    #[tool(description = "Get model infomation using model model id and model version")]
+    #[tool(
+        description = "Get model infomation using model model id and model version. All model versions come with their payload size, timestamps and schema version when known"
+    )]
     async fn get_model_stats(
         &self,
         Parameters(ModelInfoRequest {
@@ -121,11 +329,7 @@ impl ModelParserTool {
             version_number,
         }): Parameters<ModelInfoRequest>,
     ) -> String {
-        let model_parser = ModelParser::new(
-            self.app_state.get_model_cache(),
-            self.app_state.get_graph_cache(),
-            self.app_state.get_pg_pool_ref(),
-        );
+        let model_parser = self.app_state.model_parser();
         let version_number = version_number.unwrap_or("".to_string());
 
         let model_dict = model_parser
@@ -136,7 +340,7 @@ impl ModelParserTool {
             Ok(dict) => {
                 let model_stats = serde_json::to_string_pretty(&dict.model_stats).unwrap();
                 let result = ModelStatsResult {
-                    model_id: model_id,
+                    model_id,
                     stats: model_stats,
                     types: dict.get_element_types(),
                     natures: dict.get_element_nature(),
@@ -165,11 +369,7 @@ impl ModelParserTool {
             page_config,
         }): Parameters<ModelTypeQueryRequest>,
     ) -> String {
-        let model_parser = ModelParser::new(
-            self.app_state.get_model_cache(),
-            self.app_state.get_graph_cache(),
-            self.app_state.get_pg_pool_ref(),
-        );
+        let model_parser = self.app_state.model_parser();
         let version_number = version_number.unwrap_or("".to_string());
 
         let result = model_parser
@@ -223,11 +423,7 @@ impl ModelParserTool {
     ) -> String {
 
         println!("[get_element_with_filter] model id: {}, version: {:?}, filter: {:?}", model_id, version_number, filter);
-        let model_parser = ModelParser::new(
-            self.app_state.get_model_cache(),
-            self.app_state.get_graph_cache(),
-            self.app_state.get_pg_pool_ref(),
-        );
+        let model_parser = self.app_state.model_parser();
         let version_number = version_number.unwrap_or("".to_string());
 
         // Request mapping
@@ -280,6 +476,479 @@ impl ModelParserTool {
         }
     }
 
+    #[tool(
+        description = "Export the full model graph as GraphML or JSON Graph Format (JGF) into a file on the server"
+    )]
+    async fn export_model_graph(
+        &self,
+        Parameters(ModelExportRequest {
+            model_id,
+            version_number,
+            format,
+            facet_attributes,
+        }): Parameters<ModelExportRequest>,
+    ) -> String {
+        let model_parser = self.app_state.model_parser();
+        let version_number = version_number.unwrap_or("".to_string());
+        let output_dir = env::var("EXPORT_DIR").unwrap_or(EXPORT_DIR.to_string());
+
+        // Request mapping
+        let request = GraphExportFormat::parse(&format).and_then(|format| {
+            FacetAttribute::parse_all(&facet_attributes.unwrap_or_default())
+                .map(|attributes| (format, attributes))
+        });
+
+        let result = match request {
+            Ok((format, attributes)) => {
+                model_parser
+                    .export_model_graph(&model_id, &version_number, format, attributes, &output_dir)
+                    .await
+            }
+            Err(e) => Err(e),
+        };
+
+        match result {
+            Ok(result) => serde_json::to_string_pretty(&result).unwrap(),
+            Err(e) => {
+                let error = ModelStatsErrorResult {
+                    model_id,
+                    error_msg: e.to_string(),
+                };
+                serde_json::to_string_pretty(&error).unwrap()
+            }
+        }
+    }
+
+    #[tool(
+        description = "Get the most critical elements of a model ranked by degree, betweenness or pagerank centrality. On models above 256 elements, betweenness is estimated from 256 sampled source elements"
+    )]
+    async fn get_element_centrality(
+        &self,
+        Parameters(ModelCentralityRequest {
+            model_id,
+            version_number,
+            metric,
+            top_n,
+            type_,
+        }): Parameters<ModelCentralityRequest>,
+    ) -> String {
+        let model_parser = self.app_state.model_parser();
+        let version_number = version_number.unwrap_or("".to_string());
+
+        let result = match CentralityMetric::parse(&metric) {
+            Ok(metric) => {
+                model_parser
+                    .get_element_centrality(&model_id, &version_number, metric, top_n, &type_)
+                    .await
+            }
+            Err(e) => Err(e),
+        };
+
+        match result {
+            Ok(result) => serde_json::to_string_pretty(&result).unwrap(),
+            Err(e) => {
+                let error = ModelStatsErrorResult {
+                    model_id,
+                    error_msg: e.to_string(),
+                };
+                serde_json::to_string_pretty(&error).unwrap()
+            }
+        }
+    }
+
+    #[tool(
+        description = "Get elements grouped by topological layer over the selected relationship types, or the cycle preventing the ordering"
+    )]
+    async fn get_topological_layers(
+        &self,
+        Parameters(ModelLayeringRequest {
+            model_id,
+            version_number,
+            relationship_types,
+            include_isolated,
+            page_config,
+        }): Parameters<ModelLayeringRequest>,
+    ) -> String {
+        let model_parser = self.app_state.model_parser();
+        let version_number = version_number.unwrap_or("".to_string());
+
+        let result = model_parser
+            .get_topological_layers(
+                &model_id,
+                &version_number,
+                &relationship_types,
+                include_isolated.unwrap_or(false),
+                page_config,
+            )
+            .await;
+
+        match result {
+            Ok(result) => serde_json::to_string_pretty(&result).unwrap(),
+            Err(e) => {
+                let error = ModelStatsErrorResult {
+                    model_id,
+                    error_msg: e.to_string(),
+                };
+                serde_json::to_string_pretty(&error).unwrap()
+            }
+        }
+    }
+
+    #[tool(
+        description = "Get the common ancestors shared by two or more elements with the distance from every element. An element counts as its own ancestor, so an element above the others is returned at distance 0"
+    )]
+    async fn get_common_ancestors(
+        &self,
+        Parameters(ModelCommonAncestorRequest {
+            model_id,
+            version_number,
+            element_ids,
+            relationship_types,
+        }): Parameters<ModelCommonAncestorRequest>,
+    ) -> String {
+        let model_parser = self.app_state.model_parser();
+        let version_number = version_number.unwrap_or("".to_string());
+
+        let result = model_parser
+            .get_common_ancestors(
+                &model_id,
+                &version_number,
+                &element_ids,
+                &relationship_types,
+            )
+            .await;
+
+        match result {
+            Ok(result) => serde_json::to_string_pretty(&result).unwrap(),
+            Err(e) => {
+                let error = ModelStatsErrorResult {
+                    model_id,
+                    error_msg: e.to_string(),
+                };
+                serde_json::to_string_pretty(&error).unwrap()
+            }
+        }
+    }
+
+    #[tool(
+        description = "Get every downstream element impacted by a change on an element, grouped by type, with the shortest relationship chain reaching it"
+    )]
+    async fn get_downstream_impact(
+        &self,
+        Parameters(ModelImpactRequest {
+            model_id,
+            version_number,
+            element_id,
+            relationship_types,
+            depth,
+            page_config,
+        }): Parameters<ModelImpactRequest>,
+    ) -> String {
+        let model_parser = self.app_state.model_parser();
+        let version_number = version_number.unwrap_or("".to_string());
+
+        let result = model_parser
+            .get_downstream_impact(
+                &model_id,
+                &version_number,
+                &element_id,
+                &relationship_types,
+                depth.unwrap_or(MAX_IMPACT_DEPTH),
+                page_config,
+            )
+            .await;
+
+        match result {
+            Ok(result) => serde_json::to_string_pretty(&result).unwrap(),
+            Err(e) => {
+                let error = ModelStatsErrorResult {
+                    model_id,
+                    error_msg: e.to_string(),
+                };
+                serde_json::to_string_pretty(&error).unwrap()
+            }
+        }
+    }
+
+    #[tool(
+        description = "Get the ancestors and children lineage of an element, as json tokens or as a text tree"
+    )]
+    async fn get_element_lineage(
+        &self,
+        Parameters(ModelLineageRequest {
+            model_id,
+            version_number,
+            element_id,
+            ancestor_depth,
+            children_depth,
+            output_mode,
+        }): Parameters<ModelLineageRequest>,
+    ) -> String {
+        let model_parser = self.app_state.model_parser();
+        let version_number = version_number.unwrap_or("".to_string());
+
+        let result = model_parser
+            .get_element_lineage(
+                &model_id,
+                &version_number,
+                &element_id,
+                ancestor_depth.unwrap_or(LINEAGE_ANCESTOR_DEPTH),
+                children_depth.unwrap_or(LINEAGE_CHILDREN_DEPTH),
+            )
+            .await;
+
+        let result = result.and_then(|output| match output_mode.as_deref() {
+            None | Some("json") => Ok(serde_json::to_string_pretty(&output).unwrap()),
+            Some("tree") => Ok(OutputGraphRenderer::render_tree(&output)),
+            Some(output_mode) => Err(ModelError::InvalidInput(format!(
+                "Unknown output mode {}. Use json or tree",
+                output_mode
+            ))),
+        });
+
+        match result {
+            Ok(rendered) => rendered,
+            Err(e) => {
+                let error = ModelStatsErrorResult {
+                    model_id,
+                    error_msg: e.to_string(),
+                };
+                serde_json::to_string_pretty(&error).unwrap()
+            }
+        }
+    }
+
+    #[tool(
+        description = "Match a multi-hop pattern of elements and relationships and return the bound variables as rows. The search stops at 10000 rows and reports is_truncated"
+    )]
+    async fn query_pattern(
+        &self,
+        Parameters(ModelPatternQueryRequest {
+            model_id,
+            version_number,
+            pattern,
+            page_config,
+        }): Parameters<ModelPatternQueryRequest>,
+    ) -> String {
+        let model_parser = self.app_state.model_parser();
+        let version_number = version_number.unwrap_or("".to_string());
+
+        let result = model_parser
+            .query_pattern(&model_id, &version_number, &pattern, page_config)
+            .await;
+
+        match result {
+            Ok(result) => serde_json::to_string_pretty(&result).unwrap(),
+            Err(e) => {
+                let error = ModelStatsErrorResult {
+                    model_id,
+                    error_msg: e.to_string(),
+                };
+                serde_json::to_string_pretty(&error).unwrap()
+            }
+        }
+    }
+
+    #[tool(
+        description = "Get the neighbours of an element within a number of hops with the connecting relationship"
+    )]
+    async fn get_neighbours(
+        &self,
+        Parameters(ModelNeighbourRequest {
+            model_id,
+            version_number,
+            element_id,
+            direction,
+            max_hop,
+            page_config,
+        }): Parameters<ModelNeighbourRequest>,
+    ) -> String {
+        let model_parser = self.app_state.model_parser();
+        let version_number = version_number.unwrap_or("".to_string());
+
+        let result = model_parser
+            .get_neighbours(
+                &model_id,
+                &version_number,
+                &element_id,
+                direction,
+                max_hop.unwrap_or(1),
+                page_config,
+            )
+            .await;
+
+        match result {
+            Ok(result) => serde_json::to_string_pretty(&result).unwrap(),
+            Err(e) => {
+                let error = ModelStatsErrorResult {
+                    model_id,
+                    error_msg: e.to_string(),
+                };
+                serde_json::to_string_pretty(&error).unwrap()
+            }
+        }
+    }
+
+    #[tool(
+        description = "List the available model ids with their version count and latest version"
+    )]
+    async fn list_models(
+        &self,
+        Parameters(ModelListRequest {
+            model_id_prefix,
+            site_model_id,
+            page_config,
+        }): Parameters<ModelListRequest>,
+    ) -> String {
+        let model_parser = self.app_state.model_parser();
+        let model_id_prefix = model_id_prefix.unwrap_or("".to_string());
+
+        let result = model_parser
+            .list_models(&model_id_prefix, site_model_id.as_deref(), page_config)
+            .await;
+
+        match result {
+            Ok(result) => serde_json::to_string_pretty(&result).unwrap(),
+            Err(e) => {
+                let error = ModelStatsErrorResult {
+                    model_id: model_id_prefix,
+                    error_msg: e.to_string(),
+                };
+                serde_json::to_string_pretty(&error).unwrap()
+            }
+        }
+    }
+
+    #[tool(
+        description = "Check a model for duplicate ids, relationships to unknown elements, missing type or nature, empty names, versions after the model version and self-referencing relationships. Findings are grouped by severity"
+    )]
+    async fn validate_model(
+        &self,
+        Parameters(ModelValidationRequest {
+            model_id,
+            version_number,
+            page_config,
+        }): Parameters<ModelValidationRequest>,
+    ) -> String {
+        let model_parser = self.app_state.model_parser();
+        let version_number = version_number.unwrap_or("".to_string());
+
+        let result = model_parser
+            .validate_model(&model_id, &version_number, page_config)
+            .await;
+
+        match result {
+            Ok(result) => serde_json::to_string_pretty(&result).unwrap(),
+            Err(e) => {
+                let error = ModelStatsErrorResult {
+                    model_id,
+                    error_msg: e.to_string(),
+                };
+                serde_json::to_string_pretty(&error).unwrap()
+            }
+        }
+    }
+
+    #[tool(
+        description = "Query relationship facets with a json pointer, e.g. the flow rate of connections. Relationships can be filtered by element, type and nature"
+    )]
+    async fn query_relationship_facets(
+        &self,
+        Parameters(ModelRelationshipFacetRequest {
+            model_id,
+            version_number,
+            element_id,
+            type_,
+            nature,
+            facet_type,
+            query,
+            is_detail,
+            depth,
+            page_config,
+        }): Parameters<ModelRelationshipFacetRequest>,
+    ) -> String {
+        let model_parser = self.app_state.model_parser();
+        let version_number = version_number.unwrap_or("".to_string());
+
+        let result = model_parser
+            .query_relationship_facets(
+                &model_id,
+                &version_number,
+                element_id.as_deref(),
+                &type_,
+                &nature,
+                &facet_type,
+                &query,
+                is_detail,
+                depth.unwrap_or(0),
+                page_config,
+            )
+            .await;
+
+        match result {
+            Ok(result) => serde_json::to_string_pretty(&result).unwrap(),
+            Err(e) => {
+                let error = ModelStatsErrorResult {
+                    model_id,
+                    error_msg: e.to_string(),
+                };
+                serde_json::to_string_pretty(&error).unwrap()
+            }
+        }
+    }
+
+    #[tool(
+        description = "Progress of the models loaded into the cache at server start, with the state, resolved version, load time and error of each model"
+    )]
+    async fn get_cache_warming_status(&self) -> String {
+        let status = self.app_state.get_cache_warmer().get_status();
+        serde_json::to_string_pretty(&status).unwrap()
+    }
+
+    #[tool(
+        description = "Admin. Models and centrality reports in the cache with their version, estimated size and age, and the hit, miss and eviction counters"
+    )]
+    async fn get_cache_status(&self) -> String {
+        let status = CacheAdmin::get_status(&self.app_state);
+        serde_json::to_string_pretty(&status).unwrap()
+    }
+
+    #[tool(description = "Admin. Remove a model version and its centrality report from the cache")]
+    async fn evict_cached_model(
+        &self,
+        Parameters(CacheEvictRequest {
+            model_id,
+            version_number,
+        }): Parameters<CacheEvictRequest>,
+    ) -> String {
+        let result = CacheAdmin::evict(&self.app_state, &model_id, &version_number).await;
+        serde_json::to_string_pretty(&result).unwrap()
+    }
+
+    #[tool(description = "Admin. Load a model version into the cache")]
+    async fn preload_model(
+        &self,
+        Parameters(CachePreloadRequest {
+            model_id,
+            version_number,
+        }): Parameters<CachePreloadRequest>,
+    ) -> String {
+        let version_number = version_number.unwrap_or("".to_string());
+
+        let result = CacheAdmin::preload(&self.app_state, &model_id, &version_number).await;
+
+        match result {
+            Ok(result) => serde_json::to_string_pretty(&result).unwrap(),
+            Err(e) => {
+                let error = ModelStatsErrorResult {
+                    model_id,
+                    error_msg: e.to_string(),
+                };
+                serde_json::to_string_pretty(&error).unwrap()
+            }
+        }
+    }
+
     // TODO get_element_with_nature
 }
 